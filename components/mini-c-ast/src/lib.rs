@@ -1,3 +1,5 @@
+use std::fmt;
+
 use serde::Serialize;
 
 pub type Ident = String;
@@ -12,11 +14,13 @@ pub struct Program {
 pub enum Declaration {
     Variable(Vec<VariableDeclaration>),
     Function(FunctionDeclaration),
+    /// Placeholder for a declaration with syntax errors.
+    Error,
 }
 
 #[derive(Debug, Clone, Serialize)]
 pub struct VariableDeclaration {
-    pub ident: Ident,
+    pub ident: Spanned<Ident>,
     pub typ: Type,
     pub array_len: Option<usize>,
 }
@@ -28,20 +32,36 @@ pub enum Type {
     Bool,
 }
 
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let keyword = match self {
+            Self::Void => "void",
+            Self::Int => "int",
+            Self::Bool => "bool",
+        };
+        f.write_str(keyword)
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
-pub struct FunctionDeclaration(pub Type, pub Ident, pub Vec<Parameter>, pub Scope);
+pub struct FunctionDeclaration(
+    pub Type,
+    pub Spanned<Ident>,
+    pub Vec<Parameter>,
+    pub Option<Scope>,
+);
 
 impl FunctionDeclaration {
-    pub fn ident(&self) -> &Ident {
+    pub fn ident(&self) -> &Spanned<Ident> {
         &self.1
     }
 }
 
 #[derive(Debug, Clone, Serialize)]
-pub struct Parameter(pub Type, pub Ident);
+pub struct Parameter(pub Type, pub Spanned<Ident>);
 
 #[derive(Debug, Clone, Serialize)]
-pub struct Scope(pub Vec<VariableDeclaration>, pub Vec<Statement>);
+pub struct Scope(pub Vec<VariableDeclaration>, pub Vec<Spanned<Statement>>);
 
 #[derive(Debug, Clone, Serialize)]
 pub enum Statement {
@@ -51,19 +71,24 @@ pub enum Statement {
     Break,
     Return(ReturnStatement),
     Scope(Scope),
-    Expression(Expression),
+    Expression(Spanned<Expression>),
+    /// Placeholder for a statement with syntax errors.
+    Error,
 }
 #[derive(Debug, Clone, Serialize)]
-pub struct IfStatement(pub Expression, pub Scope, pub Option<Scope>);
+pub struct IfStatement(pub Spanned<Expression>, pub Scope, pub Option<Scope>);
 #[derive(Debug, Clone, Serialize)]
-pub struct ForStatement(
-    pub (Option<Expression>, Option<Expression>, Option<Expression>),
-    pub Scope,
+pub struct ForStatement(pub ForHeader, pub Scope);
+/// The initialization, condition and step of a for loop, all of them optional.
+pub type ForHeader = (
+    Option<Spanned<Expression>>,
+    Option<Spanned<Expression>>,
+    Option<Spanned<Expression>>,
 );
 #[derive(Debug, Clone, Serialize)]
-pub struct WhileStatement(pub Option<Expression>, pub Scope);
+pub struct WhileStatement(pub Option<Spanned<Expression>>, pub Scope);
 #[derive(Debug, Clone, Serialize)]
-pub struct ReturnStatement(pub Option<Expression>);
+pub struct ReturnStatement(pub Option<Spanned<Expression>>);
 
 #[derive(Debug, Clone, Serialize)]
 pub enum Expression {
@@ -75,17 +100,29 @@ pub enum Expression {
     VariableReference(Box<VariableReferenceExpression>),
 }
 #[derive(Debug, Clone, Serialize)]
-pub struct AssignmentExpression(pub VariableReferenceExpression, pub Expression);
+pub struct AssignmentExpression(pub VariableReferenceExpression, pub Spanned<Expression>);
 #[derive(Debug, Clone, Serialize)]
-pub struct BinaryExpression(pub Expression, pub BinaryOperator, pub Expression);
+pub struct BinaryExpression(
+    pub Spanned<Expression>,
+    pub BinaryOperator,
+    pub Spanned<Expression>,
+);
 #[derive(Debug, Clone, Serialize)]
-pub struct UnaryExpression(pub UnaryOperator, pub Expression);
+pub struct UnaryExpression(pub UnaryOperator, pub Spanned<Expression>);
 #[derive(Debug, Clone, Serialize)]
-pub struct FunctionCallExpression(pub Ident, pub Vec<Expression>);
+pub struct FunctionCallExpression(pub Spanned<Ident>, pub Vec<Spanned<Expression>>);
 #[derive(Debug, Clone, Serialize)]
 pub enum VariableReferenceExpression {
-    Normal(Ident),
-    Array(Ident, Expression),
+    Normal(Spanned<Ident>),
+    Array(Spanned<Ident>, Spanned<Expression>),
+}
+
+impl VariableReferenceExpression {
+    pub fn ident(&self) -> &Spanned<Ident> {
+        match self {
+            Self::Normal(ident) | Self::Array(ident, _) => ident,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -94,7 +131,7 @@ pub enum Value {
     Bool(bool),
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, PartialEq)]
 pub enum BinaryOperator {
     Add,
     Sub,
@@ -111,13 +148,34 @@ pub enum BinaryOperator {
     LessOrEquals,
 }
 
-#[derive(Debug, Clone, Serialize)]
+impl fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Mul => "*",
+            Self::Div => "/",
+            Self::Modulo => "%",
+            Self::And => "&&",
+            Self::Or => "||",
+            Self::Equals => "==",
+            Self::NotEquals => "!=",
+            Self::Greater => ">",
+            Self::GreaterOrEquals => ">=",
+            Self::Less => "<",
+            Self::LessOrEquals => "<=",
+        };
+        f.write_str(symbol)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
 pub enum UnaryOperator {
     Not,
     Negative,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Spanned<T> {
     pub inner: T,
     pub span: Span,
@@ -157,13 +215,21 @@ impl<T, E> Spanned<Result<T, E>> {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Default, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq, Default, Copy, Serialize)]
 pub struct Span(usize, usize);
 
 impl Span {
     pub fn new(l: usize, r: usize) -> Self {
         Self(l, r)
     }
+
+    pub fn start(&self) -> usize {
+        self.0
+    }
+
+    pub fn end(&self) -> usize {
+        self.1
+    }
 }
 
 impl From<Span> for std::ops::Range<usize> {
@@ -171,3 +237,64 @@ impl From<Span> for std::ops::Range<usize> {
         start..end
     }
 }
+
+/// Converts byte offsets of a source file into line and column numbers.
+#[derive(Debug, Clone)]
+pub struct LineIndex<'a> {
+    source: &'a str,
+    // Byte offset where each line starts
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(source: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+
+        Self {
+            source,
+            line_starts,
+        }
+    }
+
+    /// The 1-based line and column of the byte offset, columns count characters, not bytes.
+    pub fn line_column(&self, offset: usize) -> LineColumn {
+        let line_index = match self.line_starts.binary_search(&offset) {
+            Ok(line_index) => line_index,
+            Err(next_line_index) => next_line_index - 1,
+        };
+        let line_start = self.line_starts[line_index];
+        let column = self.source[line_start..offset].chars().count();
+
+        LineColumn {
+            line: line_index + 1,
+            column: column + 1,
+        }
+    }
+
+    /// The text of a 1-based line, without its line break.
+    pub fn line_text(&self, line: usize) -> &'a str {
+        let start = self.line_starts[line - 1];
+        let end = self
+            .line_starts
+            .get(line)
+            .copied()
+            .unwrap_or(self.source.len());
+
+        self.source[start..end].trim_end_matches(['\n', '\r'])
+    }
+}
+
+/// A 1-based position in the source, displayed as `line:column`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineColumn {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for LineColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
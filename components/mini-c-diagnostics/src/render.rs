@@ -0,0 +1,172 @@
+use std::{
+    fmt::Write,
+    io::{self, IsTerminal},
+    path::Path,
+};
+
+use mini_c_ast::{LineIndex, Span};
+
+use crate::{Diagnostic, Severity};
+
+// ANSI escape codes
+const RED: &str = "1;31";
+const YELLOW: &str = "1;33";
+const BLUE: &str = "1;34";
+const BOLD: &str = "1";
+
+/// Renders diagnostics of a single source file, in a format similar to rustc's:
+///
+/// ```text
+/// error: Redefinition of variable/parameter "a" in the same scope! (2:9)
+///  --> example.c:2:9
+///   |
+/// 1 | void f(int a) {
+///   |            - previous declaration here
+/// 2 |     int a;
+///   |         ^
+/// ```
+pub struct Renderer<'a> {
+    path: &'a Path,
+    line_index: LineIndex<'a>,
+    colors: bool,
+}
+
+/// An underlined span in the snippet.
+struct Underline<'b> {
+    span: Span,
+    marker: char,
+    color: &'static str,
+    message: &'b str,
+}
+
+impl<'a> Renderer<'a> {
+    /// Colors are turned on only if `stderr` is a terminal and `NO_COLOR` isn't set.
+    pub fn new(path: &'a Path, source: &'a str) -> Self {
+        let colors = io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+
+        Self {
+            path,
+            line_index: LineIndex::new(source),
+            colors,
+        }
+    }
+
+    pub fn with_colors(mut self, colors: bool) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let mut output = String::new();
+
+        let (severity, color) = match diagnostic.severity {
+            Severity::Error => ("error", RED),
+            Severity::Warning => ("warning", YELLOW),
+        };
+        let severity = self.paint(severity, color);
+        let message = self.paint(&diagnostic.message, BOLD);
+
+        let Some(span) = diagnostic.span else {
+            writeln!(output, "{severity}: {message}").unwrap();
+            self.render_notes(&mut output, diagnostic, 0);
+            return output;
+        };
+
+        let position = self.line_index.line_column(span.start());
+        writeln!(output, "{severity}: {message} ({position})").unwrap();
+
+        let mut underlines = vec![Underline {
+            span,
+            marker: '^',
+            color,
+            message: "",
+        }];
+        underlines.extend(diagnostic.labels.iter().map(|label| {
+            Underline {
+                span: label.span,
+                marker: '-',
+                color: BLUE,
+                message: &label.message,
+            }
+        }));
+        underlines.sort_by_key(|underline| underline.span.start());
+
+        let last_line = underlines
+            .iter()
+            .map(|underline| self.line_index.line_column(underline.span.start()).line)
+            .max()
+            .unwrap();
+        let gutter_width = last_line.to_string().len();
+        let empty_gutter = self.paint(&format!("{:gutter_width$} |", ""), BLUE);
+
+        writeln!(
+            output,
+            "{:gutter_width$}{} {}:{position}",
+            "",
+            self.paint("-->", BLUE),
+            self.path.display(),
+        )
+        .unwrap();
+        writeln!(output, "{empty_gutter}").unwrap();
+
+        let mut previous_line = None;
+        for underline in &underlines {
+            let start = self.line_index.line_column(underline.span.start());
+            let text = self.line_index.line_text(start.line);
+
+            // Lines with many underlines are only shown once
+            if previous_line != Some(start.line) {
+                if previous_line.is_some_and(|previous| previous + 1 < start.line) {
+                    writeln!(output, "{}", self.paint("...", BLUE)).unwrap();
+                }
+
+                let line_number = self.paint(&format!("{:gutter_width$} |", start.line), BLUE);
+                writeln!(output, "{line_number} {text}").unwrap();
+                previous_line = Some(start.line);
+            }
+
+            // Spans that continue in the next lines are underlined until the end of the first
+            let end = self.line_index.line_column(underline.span.end());
+            let end_column = if end.line == start.line {
+                end.column
+            } else {
+                text.chars().count() + 1
+            };
+            let length = end_column.saturating_sub(start.column).max(1);
+
+            // Keep tabs, so the underline is aligned with the text above it
+            let padding = text
+                .chars()
+                .take(start.column - 1)
+                .map(|char| if char == '\t' { '\t' } else { ' ' })
+                .collect::<String>();
+            let markers = underline.marker.to_string().repeat(length);
+            let annotation = format!("{markers} {}", underline.message);
+
+            writeln!(
+                output,
+                "{empty_gutter} {padding}{}",
+                self.paint(annotation.trim_end(), underline.color),
+            )
+            .unwrap();
+        }
+
+        self.render_notes(&mut output, diagnostic, gutter_width);
+        output
+    }
+
+    fn render_notes(&self, output: &mut String, diagnostic: &Diagnostic, gutter_width: usize) {
+        for note in &diagnostic.notes {
+            let equals = self.paint("=", BLUE);
+            writeln!(output, "{:gutter_width$} {equals} note: {note}", "").unwrap();
+        }
+    }
+
+    fn paint(&self, text: &str, code: &str) -> String {
+        if self.colors {
+            format!("\x1b[{code}m{text}\x1b[0m")
+        } else {
+            text.to_owned()
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::path::Path;
+
+use mini_c_ast::Span;
+use mini_c_diagnostics::{Diagnostic, Renderer};
+use unindent::unindent;
+
+fn render(source: &str, diagnostic: &Diagnostic) -> String {
+    Renderer::new(Path::new("example.c"), source)
+        .with_colors(false)
+        .render(diagnostic)
+}
+
+#[test]
+fn error_without_span() {
+    let diagnostic = Diagnostic::error("Something went wrong!");
+
+    assert_eq!(render("", &diagnostic), "error: Something went wrong!\n");
+}
+
+#[test]
+fn error_underlines_its_span() {
+    let source = "int main() {\n    a = 1;\n}\n";
+    let diagnostic =
+        Diagnostic::error("Variable a is not declared before use!").with_span(Span::new(17, 18));
+
+    let expected = unindent(
+        "
+        error: Variable a is not declared before use! (2:5)
+         --> example.c:2:5
+          |
+        2 |     a = 1;
+          |     ^
+        ",
+    );
+    assert_eq!(render(source, &diagnostic), expected);
+}
+
+#[test]
+fn labels_are_shown_in_source_order() {
+    let source = "void f(int a) {\n    bool b;\n    int a;\n}\n";
+    let diagnostic =
+        Diagnostic::error("Redefinition of variable/parameter \"a\" in the same scope!")
+            .with_span(Span::new(36, 37))
+            .with_label(Span::new(11, 12), "previous declaration here");
+
+    let expected = unindent(
+        "
+        error: Redefinition of variable/parameter \"a\" in the same scope! (3:9)
+         --> example.c:3:9
+          |
+        1 | void f(int a) {
+          |            - previous declaration here
+        ...
+        3 |     int a;
+          |         ^
+        ",
+    );
+    assert_eq!(render(source, &diagnostic), expected);
+}
+
+#[test]
+fn multiline_span_is_underlined_until_the_end_of_the_line() {
+    let source = "int main() {\n    f(1,\n      2);\n}\n";
+    let diagnostic =
+        Diagnostic::error("Function f() is not declared before use!").with_span(Span::new(17, 31));
+
+    let expected = unindent(
+        "
+        error: Function f() is not declared before use! (2:5)
+         --> example.c:2:5
+          |
+        2 |     f(1,
+          |     ^^^^
+        ",
+    );
+    assert_eq!(render(source, &diagnostic), expected);
+}
+
+#[test]
+fn warning_with_note() {
+    let source = "int a;\n";
+    let diagnostic = Diagnostic::warning("unused variable \"a\"")
+        .with_span(Span::new(4, 5))
+        .with_note("`-W unused_variables` is on by default");
+
+    let expected = unindent(
+        "
+        warning: unused variable \"a\" (1:5)
+         --> example.c:1:5
+          |
+        1 | int a;
+          |     ^
+          = note: `-W unused_variables` is on by default
+        ",
+    );
+    assert_eq!(render(source, &diagnostic), expected);
+}
@@ -1,3 +1,5 @@
+use std::fmt;
+
 use logos::Logos;
 
 pub type LexerResult<'a> = std::result::Result<TokenWithSpan<'a>, LexerError>;
@@ -9,8 +11,8 @@ pub type LexerError = MiniCParseError;
 pub enum MiniCParseError {
     // This is, arguably, not a parser error lol
     InvalidToken(String, std::ops::Range<usize>),
-    InvalidType(String),
-    InvalidInteger(String, std::num::ParseIntError),
+    InvalidType(String, std::ops::Range<usize>),
+    InvalidInteger(String, std::num::ParseIntError, std::ops::Range<usize>),
 }
 
 pub struct Lexer<'a> {
@@ -123,3 +125,47 @@ pub enum Token<'input> {
     #[regex(r"\s+", logos::skip)]
     Invalid,
 }
+
+impl fmt::Display for Token<'_> {
+    /// Shows the token as it's written in the source.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Self::Ident(text) | Self::Int(text) => text,
+            Self::IncludeMinicio => "#include",
+            Self::MinicioHeader => r#""minicio.h""#,
+            Self::Return => "return",
+            Self::If => "if",
+            Self::Else => "else",
+            Self::For => "for",
+            Self::While => "while",
+            Self::Break => "break",
+            Self::True => "true",
+            Self::False => "false",
+            Self::SemiColon => ";",
+            Self::SingleEquals => "=",
+            Self::Comma => ",",
+            Self::OpenParenthesis => "(",
+            Self::CloseParenthesis => ")",
+            Self::OpenBraces => "{",
+            Self::CloseBraces => "}",
+            Self::OpenBrackets => "[",
+            Self::CloseBrackets => "]",
+            Self::Plus => "+",
+            Self::Minus => "-",
+            Self::Star => "*",
+            Self::Slash => "/",
+            Self::Modulo => "%",
+            Self::And => "&&",
+            Self::Or => "||",
+            Self::Not => "!",
+            Self::Less => "<",
+            Self::LessOrEqual => "<=",
+            Self::DoubleEquals => "==",
+            Self::NotEquals => "!=",
+            Self::Greater => ">",
+            Self::GreaterOrEqual => ">=",
+            Self::Invalid => "<invalid>",
+        };
+        f.write_str(text)
+    }
+}
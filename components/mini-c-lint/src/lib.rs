@@ -0,0 +1,160 @@
+//! Lints find code that is valid, but probably a mistake, they run after the semantic analysis
+//! and produce warnings instead of errors, unless they're denied.
+
+mod linter;
+mod uninitialized;
+
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use mini_c_ast::Program;
+use mini_c_diagnostics::{Diagnostic, Severity};
+
+use self::{linter::Linter, uninitialized::InitializationChecker};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lint {
+    UnusedVariables,
+    UnusedParameters,
+    UnusedFunctions,
+    UnreachableCode,
+    Shadowing,
+    UninitializedVariables,
+}
+
+impl Lint {
+    pub const ALL: [Lint; 6] = [
+        Lint::UnusedVariables,
+        Lint::UnusedParameters,
+        Lint::UnusedFunctions,
+        Lint::UnreachableCode,
+        Lint::Shadowing,
+        Lint::UninitializedVariables,
+    ];
+
+    /// The name used to refer to the lint in the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::UnusedVariables => "unused_variables",
+            Self::UnusedParameters => "unused_parameters",
+            Self::UnusedFunctions => "unused_functions",
+            Self::UnreachableCode => "unreachable_code",
+            Self::Shadowing => "shadowing",
+            Self::UninitializedVariables => "uninitialized_variables",
+        }
+    }
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Lint {
+    type Err = UnknownLint;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|lint| lint.name() == name)
+            .ok_or_else(|| UnknownLint(name.to_owned()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownLint(pub String);
+
+impl fmt::Display for UnknownLint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown lint \"{}\"", self.0)
+    }
+}
+
+impl std::error::Error for UnknownLint {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl Level {
+    /// The command line flag that sets a lint to this level.
+    pub fn flag(self) -> &'static str {
+        match self {
+            Self::Allow => "-A",
+            Self::Warn => "-W",
+            Self::Deny => "-D",
+        }
+    }
+}
+
+/// The level of each lint, all of them warn by default.
+#[derive(Debug, Clone, Default)]
+pub struct LintLevels {
+    levels: HashMap<Lint, Level>,
+}
+
+impl LintLevels {
+    pub fn set(&mut self, lint: Lint, level: Level) {
+        self.levels.insert(lint, level);
+    }
+
+    pub fn set_all(&mut self, level: Level) {
+        for lint in Lint::ALL {
+            self.set(lint, level);
+        }
+    }
+
+    pub fn get(&self, lint: Lint) -> Level {
+        self.levels.get(&lint).copied().unwrap_or(Level::Warn)
+    }
+
+    /// Give the diagnostic of a lint the severity of its level, allowed lints are dropped.
+    fn apply(&self, lint: Lint, diagnostic: Diagnostic) -> Option<Diagnostic> {
+        let (severity, note) = match (self.get(lint), self.levels.get(&lint)) {
+            (Level::Allow, _) => return None,
+            (Level::Warn, None) => (Severity::Warning, format!("`-W {lint}` is on by default")),
+            (level @ Level::Warn, Some(_)) => {
+                (
+                    Severity::Warning,
+                    format!("requested by `{} {lint}`", level.flag()),
+                )
+            }
+            (level @ Level::Deny, _) => {
+                (
+                    Severity::Error,
+                    format!("requested by `{} {lint}`", level.flag()),
+                )
+            }
+        };
+
+        Some(Diagnostic {
+            severity,
+            ..diagnostic.with_note(note)
+        })
+    }
+}
+
+/// Run all lints that aren't allowed in a program that passed the semantic analysis.
+///
+/// The diagnostics of denied lints are errors, the others are warnings.
+pub fn lint(program: &Program, levels: &LintLevels) -> Vec<Diagnostic> {
+    let mut linter = Linter::new();
+    linter.lint_program(program);
+
+    let mut initialization_checker = InitializationChecker::new();
+    initialization_checker.check_program(program);
+
+    let mut findings = linter.findings;
+    findings.extend(initialization_checker.findings);
+
+    // Some lints only find problems at the end of a scope, show them in the order of the code
+    findings.sort_by_key(|(_, diagnostic)| diagnostic.span.map(|span| span.start()));
+
+    findings
+        .into_iter()
+        .filter_map(|(lint, diagnostic)| levels.apply(lint, diagnostic))
+        .collect()
+}
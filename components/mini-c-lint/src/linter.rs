@@ -0,0 +1,278 @@
+use std::collections::HashSet;
+
+use mini_c_ast::{
+    AssignmentExpression, BinaryExpression, Declaration, Expression, ForStatement,
+    FunctionCallExpression, FunctionDeclaration, Ident, IfStatement, Parameter, Program,
+    ReturnStatement, Scope, Span, Spanned, Statement, UnaryExpression, VariableDeclaration,
+    VariableReferenceExpression, WhileStatement,
+};
+use mini_c_diagnostics::Diagnostic;
+use mini_c_sema::symbols::{Symbol, SymbolTable};
+
+use crate::Lint;
+
+/// Walks the AST once, looking for everything that the lints check.
+pub(crate) struct Linter<'a> {
+    variables: SymbolTable<Variable<'a>>,
+    // Definitions of functions, in the order they appear
+    functions: Vec<&'a Spanned<Ident>>,
+    // Functions called by other functions, recursive calls don't count
+    called_functions: HashSet<&'a str>,
+    // Name of the function being linted
+    current_function: Option<&'a str>,
+    pub findings: Vec<(Lint, Diagnostic)>,
+}
+
+struct Variable<'a> {
+    ident: &'a Spanned<Ident>,
+    kind: VariableKind<'a>,
+    used: bool,
+}
+
+impl Symbol for Variable<'_> {
+    fn ident(&self) -> &Spanned<Ident> {
+        self.ident
+    }
+}
+
+enum VariableKind<'a> {
+    Global,
+    Local,
+    Parameter { function: &'a str },
+}
+
+impl<'a> Linter<'a> {
+    pub fn new() -> Self {
+        Self {
+            variables: SymbolTable::new(),
+            functions: vec![],
+            called_functions: HashSet::new(),
+            current_function: None,
+            findings: vec![],
+        }
+    }
+
+    fn report(&mut self, lint: Lint, diagnostic: Diagnostic) {
+        self.findings.push((lint, diagnostic));
+    }
+
+    pub fn lint_program(&mut self, program: &'a Program) {
+        // The outermost scope holds the global variables
+        self.variables.push_scope();
+
+        for declaration in &program.declarations {
+            match declaration {
+                Declaration::Variable(variables) => {
+                    for variable in variables {
+                        self.declare_variable(&variable.ident, VariableKind::Global);
+                    }
+                }
+                Declaration::Function(FunctionDeclaration(_, ident, parameters, Some(scope))) => {
+                    self.functions.push(ident);
+                    self.lint_function(ident, parameters, scope);
+                }
+                Declaration::Function(FunctionDeclaration(.., None)) | Declaration::Error => {}
+            }
+        }
+
+        self.pop_scope();
+
+        let unused_functions = self
+            .functions
+            .iter()
+            .filter(|ident| ident.inner != "main")
+            .filter(|ident| !self.called_functions.contains(ident.inner.as_str()))
+            .map(|ident| {
+                Diagnostic::warning(format!("function \"{}()\" is never called", ident.inner))
+                    .with_span(ident.span)
+            })
+            .collect::<Vec<_>>();
+        for diagnostic in unused_functions {
+            self.report(Lint::UnusedFunctions, diagnostic);
+        }
+    }
+
+    fn lint_function(
+        &mut self,
+        ident: &'a Spanned<Ident>,
+        parameters: &'a [Parameter],
+        scope: &'a Scope,
+    ) {
+        self.current_function = Some(&ident.inner);
+
+        // Parameters share the scope of the function body
+        self.variables.push_scope();
+        for Parameter(_, parameter) in parameters {
+            let kind = VariableKind::Parameter {
+                function: &ident.inner,
+            };
+            self.declare_variable(parameter, kind);
+        }
+        self.lint_scope_body(scope);
+        self.pop_scope();
+
+        self.current_function = None;
+    }
+
+    fn declare_variable(&mut self, ident: &'a Spanned<Ident>, kind: VariableKind<'a>) {
+        if let Some(shadowed) = self.variables.get_shadowed(&ident.inner) {
+            let diagnostic =
+                Diagnostic::warning(format!("\"{}\" shadows an outer variable", ident.inner))
+                    .with_span(ident.span)
+                    .with_label(shadowed.ident.span, "shadowed variable declared here");
+            self.report(Lint::Shadowing, diagnostic);
+        }
+
+        // Redefinitions are reported by the semantic analysis, the first one is the one used
+        let _ = self.variables.declare(Variable {
+            ident,
+            kind,
+            used: false,
+        });
+    }
+
+    /// Pop the innermost scope, reporting its variables that were never used.
+    fn pop_scope(&mut self) {
+        let scope = self.variables.pop_scope();
+
+        for variable in scope.into_iter().filter(|variable| !variable.used) {
+            let ident = &variable.ident.inner;
+
+            let (lint, message) = match variable.kind {
+                VariableKind::Global => {
+                    (
+                        Lint::UnusedVariables,
+                        format!("unused global variable \"{ident}\""),
+                    )
+                }
+                VariableKind::Local => {
+                    (
+                        Lint::UnusedVariables,
+                        format!("unused variable \"{ident}\""),
+                    )
+                }
+                VariableKind::Parameter { function } => {
+                    (
+                        Lint::UnusedParameters,
+                        format!("unused parameter \"{ident}\" of function \"{function}()\""),
+                    )
+                }
+            };
+
+            self.report(
+                lint,
+                Diagnostic::warning(message).with_span(variable.ident.span),
+            );
+        }
+    }
+
+    fn lint_scope(&mut self, scope: &'a Scope) {
+        self.variables.push_scope();
+        self.lint_scope_body(scope);
+        self.pop_scope();
+    }
+
+    fn lint_scope_body(&mut self, scope: &'a Scope) {
+        let Scope(variable_declarations, statements) = scope;
+
+        for VariableDeclaration { ident, .. } in variable_declarations {
+            self.declare_variable(ident, VariableKind::Local);
+        }
+
+        // Only the first unreachable statement is reported, it covers the rest of the scope
+        let jump = statements.iter().position(|statement| {
+            matches!(statement.inner, Statement::Return(_) | Statement::Break)
+        });
+        if let Some(jump) = jump.filter(|jump| jump + 1 < statements.len()) {
+            let unreachable = Span::new(
+                statements[jump + 1].span.start(),
+                statements.last().unwrap().span.end(),
+            );
+            let diagnostic = Diagnostic::warning("unreachable statement")
+                .with_span(unreachable)
+                .with_label(statements[jump].span, "any code after this is unreachable");
+            self.report(Lint::UnreachableCode, diagnostic);
+        }
+
+        for statement in statements {
+            self.lint_statement(statement);
+        }
+    }
+
+    fn lint_statement(&mut self, statement: &'a Spanned<Statement>) {
+        match &statement.inner {
+            Statement::If(IfStatement(condition, then_scope, else_scope)) => {
+                self.lint_expression(condition);
+                self.lint_scope(then_scope);
+                if let Some(else_scope) = else_scope {
+                    self.lint_scope(else_scope);
+                }
+            }
+            Statement::For(ForStatement((initialization, condition, step), scope)) => {
+                for expression in [initialization, condition, step].into_iter().flatten() {
+                    self.lint_expression(expression);
+                }
+                self.lint_scope(scope);
+            }
+            Statement::While(WhileStatement(condition, scope)) => {
+                if let Some(condition) = condition {
+                    self.lint_expression(condition);
+                }
+                self.lint_scope(scope);
+            }
+            Statement::Return(ReturnStatement(expression)) => {
+                if let Some(expression) = expression {
+                    self.lint_expression(expression);
+                }
+            }
+            Statement::Scope(scope) => self.lint_scope(scope),
+            Statement::Expression(expression) => self.lint_expression(expression),
+            Statement::Break | Statement::Error => {}
+        }
+    }
+
+    fn lint_expression(&mut self, expression: &'a Spanned<Expression>) {
+        match &expression.inner {
+            Expression::Value(_) => {}
+            Expression::Assignment(assignment) => {
+                let AssignmentExpression(variable_reference, value) = assignment.as_ref();
+                // Assigning to a variable doesn't use it, only the index is read
+                if let VariableReferenceExpression::Array(_, index) = variable_reference {
+                    self.lint_expression(index);
+                }
+                self.lint_expression(value);
+            }
+            Expression::Binary(binary) => {
+                let BinaryExpression(lhs, _, rhs) = binary.as_ref();
+                self.lint_expression(lhs);
+                self.lint_expression(rhs);
+            }
+            Expression::Unary(unary) => {
+                let UnaryExpression(_, operand) = unary.as_ref();
+                self.lint_expression(operand);
+            }
+            Expression::FunctionCall(FunctionCallExpression(ident, arguments)) => {
+                if self.current_function != Some(ident.inner.as_str()) {
+                    self.called_functions.insert(&ident.inner);
+                }
+                for argument in arguments {
+                    self.lint_expression(argument);
+                }
+            }
+            Expression::VariableReference(variable_reference) => {
+                self.lint_variable_reference(variable_reference);
+            }
+        }
+    }
+
+    fn lint_variable_reference(&mut self, variable_reference: &'a VariableReferenceExpression) {
+        if let VariableReferenceExpression::Array(_, index) = variable_reference {
+            self.lint_expression(index);
+        }
+
+        let ident = &variable_reference.ident().inner;
+        if let Some(variable) = self.variables.get_mut(ident) {
+            variable.used = true;
+        }
+    }
+}
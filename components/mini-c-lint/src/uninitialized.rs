@@ -0,0 +1,328 @@
+use std::{collections::HashSet, mem};
+
+use mini_c_ast::{
+    AssignmentExpression, BinaryExpression, BinaryOperator, Declaration, Expression, ForStatement,
+    FunctionCallExpression, FunctionDeclaration, Ident, IfStatement, Parameter, Program,
+    ReturnStatement, Scope, Spanned, Statement, UnaryExpression, VariableDeclaration,
+    VariableReferenceExpression, WhileStatement,
+};
+use mini_c_diagnostics::Diagnostic;
+use mini_c_sema::{
+    cfg::is_infinite_loop,
+    symbols::{Symbol, SymbolTable},
+};
+
+use crate::Lint;
+
+/// Definite-assignment analysis, finds reads of local variables that might happen before a
+/// value is assigned to them.
+///
+/// Function bodies are walked following the flow of the program, tracking which locals were
+/// assigned in every path that reaches each point. Loop bodies are walked twice, the second time
+/// with what the previous iterations might have assigned.
+pub(crate) struct InitializationChecker<'a> {
+    variables: SymbolTable<Variable<'a>>,
+    state: State,
+    // Merged states of the `break`s of each loop being checked, the innermost is the last
+    loop_exits: Vec<State>,
+    // Each variable is reported only at its first bad read
+    reported: HashSet<usize>,
+    pub findings: Vec<(Lint, Diagnostic)>,
+}
+
+struct Variable<'a> {
+    ident: &'a Spanned<Ident>,
+    // Parameters and arrays are always considered initialized
+    tracked: bool,
+}
+
+impl Symbol for Variable<'_> {
+    fn ident(&self) -> &Spanned<Ident> {
+        self.ident
+    }
+}
+
+impl Variable<'_> {
+    // Identifiers of declarations never overlap, so their positions tell variables apart
+    fn id(&self) -> usize {
+        self.ident.span.start()
+    }
+}
+
+/// What is known about the assignments at some point of a function.
+#[derive(Debug, Clone, Default)]
+struct State {
+    // Variables assigned in every path that reaches this point
+    assigned: HashSet<usize>,
+    // Variables assigned in at least one of them
+    maybe_assigned: HashSet<usize>,
+    // No path reaches this point, like the code after a `return`
+    unreachable: bool,
+}
+
+impl State {
+    fn unreachable() -> Self {
+        Self {
+            unreachable: true,
+            ..Self::default()
+        }
+    }
+
+    fn assign(&mut self, id: usize) {
+        self.assigned.insert(id);
+        self.maybe_assigned.insert(id);
+    }
+
+    /// The state at a point reached by the paths of both `self` and `other`.
+    fn merge(self, other: Self) -> Self {
+        if self.unreachable {
+            return other;
+        }
+        if other.unreachable {
+            return self;
+        }
+
+        Self {
+            assigned: &self.assigned & &other.assigned,
+            maybe_assigned: &self.maybe_assigned | &other.maybe_assigned,
+            unreachable: false,
+        }
+    }
+}
+
+impl<'a> InitializationChecker<'a> {
+    pub fn new() -> Self {
+        Self {
+            variables: SymbolTable::new(),
+            state: State::default(),
+            loop_exits: vec![],
+            reported: HashSet::new(),
+            findings: vec![],
+        }
+    }
+
+    pub fn check_program(&mut self, program: &'a Program) {
+        // Globals aren't tracked, so they're never declared here
+        for declaration in &program.declarations {
+            if let Declaration::Function(FunctionDeclaration(_, _, parameters, Some(scope))) =
+                declaration
+            {
+                self.check_function(parameters, scope);
+            }
+        }
+    }
+
+    fn check_function(&mut self, parameters: &'a [Parameter], scope: &'a Scope) {
+        self.state = State::default();
+
+        // Parameters share the scope of the function body, they still need to be declared
+        // because they shadow globals and are shadowed by locals
+        self.variables.push_scope();
+        for Parameter(_, ident) in parameters {
+            self.declare_variable(ident, false);
+        }
+        self.check_scope_body(scope);
+        self.variables.pop_scope();
+    }
+
+    fn check_scope(&mut self, scope: &'a Scope) {
+        self.variables.push_scope();
+        self.check_scope_body(scope);
+        self.variables.pop_scope();
+    }
+
+    fn check_scope_body(&mut self, scope: &'a Scope) {
+        let Scope(variable_declarations, statements) = scope;
+
+        for VariableDeclaration {
+            ident, array_len, ..
+        } in variable_declarations
+        {
+            self.declare_variable(ident, array_len.is_none());
+        }
+
+        for statement in statements {
+            self.check_statement(&statement.inner);
+        }
+    }
+
+    fn declare_variable(&mut self, ident: &'a Spanned<Ident>, tracked: bool) {
+        // Redefinitions are reported by the semantic analysis, the first one is the one used
+        let _ = self.variables.declare(Variable { ident, tracked });
+    }
+
+    fn check_statement(&mut self, statement: &'a Statement) {
+        match statement {
+            Statement::If(IfStatement(condition, then_scope, else_scope)) => {
+                self.check_expression(condition);
+
+                let before_then = self.state.clone();
+                self.check_scope(then_scope);
+                let after_then = mem::replace(&mut self.state, before_then);
+                if let Some(else_scope) = else_scope {
+                    self.check_scope(else_scope);
+                }
+
+                let after_else = mem::take(&mut self.state);
+                self.state = after_then.merge(after_else);
+            }
+            Statement::For(ForStatement((initialization, condition, step), scope)) => {
+                if let Some(initialization) = initialization {
+                    self.check_expression(initialization);
+                }
+                self.check_loop(condition.as_ref(), step.as_ref(), scope);
+            }
+            Statement::While(WhileStatement(condition, scope)) => {
+                self.check_loop(condition.as_ref(), None, scope);
+            }
+            Statement::Return(ReturnStatement(expression)) => {
+                if let Some(expression) = expression {
+                    self.check_expression(expression);
+                }
+                self.state = State::unreachable();
+            }
+            Statement::Break => {
+                let state = mem::replace(&mut self.state, State::unreachable());
+
+                // The semantic analysis reports a `break` outside of a loop, ignore it here
+                if let Some(exit) = self.loop_exits.last_mut() {
+                    *exit = mem::take(exit).merge(state);
+                }
+            }
+            Statement::Scope(scope) => self.check_scope(scope),
+            Statement::Expression(expression) => self.check_expression(expression),
+            Statement::Error => {}
+        }
+    }
+
+    fn check_loop(
+        &mut self,
+        condition: Option<&'a Spanned<Expression>>,
+        step: Option<&'a Spanned<Expression>>,
+        scope: &'a Scope,
+    ) {
+        if let Some(condition) = condition {
+            self.check_expression(condition);
+        }
+
+        // The loop is left when the condition is false, or only through `break`s if it's
+        // infinite
+        let is_infinite = is_infinite_loop(condition);
+        let exit = if is_infinite {
+            State::unreachable()
+        } else {
+            self.state.clone()
+        };
+
+        // Assignments at the end of the body reach its start through the back-edge, so the
+        // body is walked once just to find them, and again from the merged state to report
+        // the reads. The second walk can't assign anything new, so that's the fixpoint
+        let findings = self.findings.len();
+        let reported = self.reported.clone();
+        let start_of_body = self.state.clone();
+        self.loop_exits.push(State::unreachable());
+        self.check_body(step, scope);
+        self.loop_exits.pop();
+        self.findings.truncate(findings);
+        self.reported = reported;
+        self.state = start_of_body.merge(mem::take(&mut self.state));
+
+        self.loop_exits.push(exit);
+        self.check_body(step, scope);
+
+        // In infinite loops, the end of the body goes back to the start and never leaves
+        let end_of_body = mem::take(&mut self.state);
+        let exit = self.loop_exits.pop().unwrap();
+        self.state = if is_infinite {
+            exit
+        } else {
+            exit.merge(end_of_body)
+        };
+    }
+
+    fn check_body(&mut self, step: Option<&'a Spanned<Expression>>, scope: &'a Scope) {
+        self.check_scope(scope);
+        if let Some(step) = step {
+            self.check_expression(step);
+        }
+    }
+
+    fn check_expression(&mut self, expression: &'a Spanned<Expression>) {
+        match &expression.inner {
+            Expression::Value(_) => {}
+            Expression::Assignment(assignment) => {
+                let AssignmentExpression(variable_reference, value) = assignment.as_ref();
+                if let VariableReferenceExpression::Array(_, index) = variable_reference {
+                    self.check_expression(index);
+                }
+                self.check_expression(value);
+
+                if let Some(variable) = self.variables.get(&variable_reference.ident().inner) {
+                    let id = variable.id();
+                    self.state.assign(id);
+                }
+            }
+            Expression::Binary(binary) => {
+                let BinaryExpression(lhs, operator, rhs) = binary.as_ref();
+                self.check_expression(lhs);
+
+                // The right-hand side of `&&` and `||` isn't always evaluated
+                if let BinaryOperator::And | BinaryOperator::Or = operator {
+                    let before_rhs = self.state.clone();
+                    self.check_expression(rhs);
+                    let after_rhs = mem::take(&mut self.state);
+                    self.state = before_rhs.merge(after_rhs);
+                } else {
+                    self.check_expression(rhs);
+                }
+            }
+            Expression::Unary(unary) => {
+                let UnaryExpression(_, operand) = unary.as_ref();
+                self.check_expression(operand);
+            }
+            Expression::FunctionCall(FunctionCallExpression(_, arguments)) => {
+                for argument in arguments {
+                    self.check_expression(argument);
+                }
+            }
+            Expression::VariableReference(variable_reference) => {
+                if let VariableReferenceExpression::Array(_, index) = variable_reference.as_ref() {
+                    self.check_expression(index);
+                }
+                self.check_read(variable_reference.ident());
+            }
+        }
+    }
+
+    fn check_read(&mut self, ident: &Spanned<Ident>) {
+        let Some(variable) = self.variables.get(&ident.inner) else {
+            return;
+        };
+        let id = variable.id();
+        let declaration = variable.ident.span;
+
+        if !variable.tracked || self.state.unreachable || self.state.assigned.contains(&id) {
+            return;
+        }
+        if !self.reported.insert(id) {
+            return;
+        }
+
+        let message = if self.state.maybe_assigned.contains(&id) {
+            format!(
+                "variable \"{}\" might be read before being initialized",
+                ident.inner
+            )
+        } else {
+            format!(
+                "variable \"{}\" is read before being initialized",
+                ident.inner
+            )
+        };
+        let diagnostic = Diagnostic::warning(message)
+            .with_span(ident.span)
+            .with_label(declaration, "declared here without a value");
+        self.findings
+            .push((Lint::UninitializedVariables, diagnostic));
+    }
+}
@@ -0,0 +1,371 @@
+use mini_c_diagnostics::Diagnostic;
+use mini_c_lint::{Level, Lint, LintLevels};
+
+fn lint_with(input: &str, levels: &LintLevels) -> Vec<Diagnostic> {
+    let (ast, syntax_errors) = mini_c_parser::parse(input);
+    assert!(
+        syntax_errors.is_empty(),
+        "Failed to parse program: {syntax_errors:?}"
+    );
+    mini_c_sema::check(&ast).expect("Program has semantic errors");
+
+    mini_c_lint::lint(&ast, levels)
+}
+
+fn lint_messages(input: &str) -> Vec<String> {
+    lint_with(input, &LintLevels::default())
+        .into_iter()
+        .map(|diagnostic| diagnostic.message)
+        .collect()
+}
+
+#[test]
+fn used_code_has_no_warnings() {
+    let input = "
+        int g;
+        int add(int a, int b) { return a + b; }
+        int main() {
+            int i;
+            for (i = 0; i < 3; i = i + 1) {
+                g = add(g, i);
+            }
+            return g;
+        }
+    ";
+
+    assert_eq!(lint_messages(input), Vec::<String>::new());
+}
+
+#[test]
+fn unused_variables_and_parameters() {
+    let input = "
+        int g;
+        int f(int x) { int a; return 0; }
+        int main() { return f(1); }
+    ";
+
+    assert_eq!(
+        lint_messages(input),
+        [
+            "unused global variable \"g\"",
+            "unused parameter \"x\" of function \"f()\"",
+            "unused variable \"a\"",
+        ]
+    );
+}
+
+#[test]
+fn assigned_variables_are_unused_until_read() {
+    let input = "int main() { int a; a = 1; return 0; }";
+
+    assert_eq!(lint_messages(input), ["unused variable \"a\""]);
+}
+
+#[test]
+fn recursive_functions_are_never_called() {
+    let input = "
+        int f(int n) { return f(n - 1); }
+        void g() {}
+        int main() { return 0; }
+    ";
+
+    assert_eq!(
+        lint_messages(input),
+        [
+            "function \"f()\" is never called",
+            "function \"g()\" is never called",
+        ]
+    );
+}
+
+#[test]
+fn unreachable_statements_after_return_and_break() {
+    let input = "
+        int main() {
+            while (true) {
+                break;
+                main();
+            }
+            return 0;
+            main();
+            main();
+        }
+    ";
+
+    let diagnostics = lint_with(input, &LintLevels::default());
+    let messages = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.message.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(messages, ["unreachable statement", "unreachable statement"]);
+
+    // The second one covers both calls after the return
+    let span = diagnostics[1].span.unwrap();
+    assert_eq!(
+        &input[span.start()..span.end()],
+        "main();\n            main();"
+    );
+    assert_eq!(
+        diagnostics[1].labels[0].message,
+        "any code after this is unreachable"
+    );
+}
+
+#[test]
+fn shadowing_points_at_the_outer_variable() {
+    let input = "
+        int a;
+        int main() {
+            int b;
+            b = 0;
+            {
+                int b;
+                b = a;
+                print(b);
+            }
+            return b;
+        }
+    ";
+
+    let diagnostics = lint_with(input, &LintLevels::default());
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "\"b\" shadows an outer variable");
+
+    let label = diagnostics[0].labels[0].span;
+    assert_eq!(label.start(), input.find("int b").unwrap() + 4);
+}
+
+#[test]
+fn levels_change_the_severity() {
+    let input = "int main() { int a; return 0; }";
+
+    let diagnostics = lint_with(input, &LintLevels::default());
+    assert!(!diagnostics[0].is_error());
+    assert_eq!(
+        diagnostics[0].notes,
+        ["`-W unused_variables` is on by default"]
+    );
+
+    let mut levels = LintLevels::default();
+    levels.set(Lint::UnusedVariables, Level::Deny);
+    let diagnostics = lint_with(input, &levels);
+    assert!(diagnostics[0].is_error());
+    assert_eq!(diagnostics[0].notes, ["requested by `-D unused_variables`"]);
+
+    levels.set_all(Level::Allow);
+    assert_eq!(lint_with(input, &levels), []);
+}
+
+#[test]
+fn lint_names_round_trip() {
+    for lint in Lint::ALL {
+        assert_eq!(lint.name().parse(), Ok(lint));
+    }
+
+    let error = "unused".parse::<Lint>().unwrap_err();
+    assert_eq!(error.to_string(), "unknown lint \"unused\"");
+}
+
+#[test]
+fn reads_before_any_assignment() {
+    let input = "
+        int g;
+        int f(int x) { return x + g; }
+        int main() {
+            int a;
+            int b;
+            b = a;
+            return f(b);
+        }
+    ";
+
+    let diagnostics = lint_with(input, &LintLevels::default());
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message,
+        "variable \"a\" is read before being initialized"
+    );
+    assert_eq!(
+        diagnostics[0].labels[0].message,
+        "declared here without a value"
+    );
+}
+
+#[test]
+fn reads_after_assignments_in_some_paths() {
+    let input = "
+        int main() {
+            int a;
+            int b;
+            int c;
+            if (true) {
+                a = 1;
+                b = 1;
+            } else {
+                b = 2;
+            }
+            while (b < 10) {
+                c = 1;
+                b = b + 1;
+            }
+            return a + b + c;
+        }
+    ";
+
+    assert_eq!(
+        lint_messages(input),
+        [
+            "variable \"a\" might be read before being initialized",
+            "variable \"c\" might be read before being initialized",
+        ]
+    );
+}
+
+#[test]
+fn loops_without_condition_exit_through_break() {
+    let input = "
+        int main() {
+            int a;
+            int b;
+            while () {
+                a = 1;
+                if (a == 1) {
+                    b = 1;
+                    break;
+                }
+                return 0;
+            }
+            return a + b;
+        }
+    ";
+
+    assert_eq!(lint_messages(input), Vec::<String>::new());
+}
+
+#[test]
+fn loops_with_true_condition_exit_through_break() {
+    let input = "
+        int main() {
+            int x;
+            while (true) {
+                x = 1;
+                break;
+            }
+            return x;
+        }
+    ";
+
+    assert_eq!(lint_messages(input), Vec::<String>::new());
+}
+
+#[test]
+fn right_hand_side_of_logical_operators_might_not_run() {
+    let input = "
+        int main() {
+            int a;
+            bool b;
+            if (false && (b = true)) {
+                a = 0;
+            }
+            if (b) {
+                return a;
+            }
+            return 0;
+        }
+    ";
+
+    assert_eq!(
+        lint_messages(input),
+        [
+            "variable \"b\" might be read before being initialized",
+            "variable \"a\" might be read before being initialized",
+        ]
+    );
+}
+
+#[test]
+fn assignments_reach_the_next_iterations() {
+    let input = "
+        int main() {
+            int i;
+            int x;
+            i = 0;
+            while (i < 3) {
+                if (i > 0) {
+                    print(x);
+                }
+                x = 1;
+                i = i + 1;
+            }
+            return 0;
+        }
+    ";
+
+    assert_eq!(
+        lint_messages(input),
+        ["variable \"x\" might be read before being initialized"]
+    );
+}
+
+#[test]
+fn shadowing_variables_are_tracked_separately() {
+    let input = "
+        int main() {
+            int a;
+            a = 1;
+            print(a);
+            {
+                int a;
+                return a;
+            }
+        }
+    ";
+
+    let diagnostics = lint_with(input, &LintLevels::default());
+    let messages = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.message.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        [
+            "\"a\" shadows an outer variable",
+            "variable \"a\" is read before being initialized",
+        ]
+    );
+}
+
+#[test]
+fn examples_initialize_their_variables() {
+    let examples = concat!(env!("CARGO_MANIFEST_DIR"), "/../../examples");
+
+    let mut levels = LintLevels::default();
+    levels.set_all(Level::Allow);
+    levels.set(Lint::UninitializedVariables, Level::Warn);
+
+    for entry in std::fs::read_dir(examples).unwrap() {
+        let path = entry.unwrap().path();
+        let input = std::fs::read_to_string(&path).unwrap();
+
+        // Some examples have semantic errors, but they can still be linted
+        let (ast, syntax_errors) = mini_c_parser::parse(&input);
+        assert!(syntax_errors.is_empty(), "{}", path.display());
+
+        let diagnostics = mini_c_lint::lint(&ast, &levels);
+        assert_eq!(diagnostics, [], "{}", path.display());
+    }
+}
+
+#[test]
+fn break_outside_of_loop_is_ignored() {
+    let (ast, syntax_errors) = mini_c_parser::parse("int main() { break; return 0; }");
+    assert!(syntax_errors.is_empty());
+
+    // The semantic analysis rejects this program, but linting it must not panic
+    let levels = LintLevels::default();
+    let messages = mini_c_lint::lint(&ast, &levels)
+        .into_iter()
+        .map(|diagnostic| diagnostic.message)
+        .collect::<Vec<_>>();
+    assert_eq!(messages, ["unreachable statement"]);
+}
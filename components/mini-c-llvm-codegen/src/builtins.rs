@@ -1,5 +1,8 @@
 pub const BUILTINS_IR: &str = include_str!("builtins.ll");
 
+// Only linked in when the program has `#include "minicio.h"`.
+pub const MINICIO_IR: &str = include_str!("minicio.ll");
+
 // The code that originally generated the IR above, before I edited it.
 /*
 fn compile_builtins(&mut self) {
@@ -1,48 +1,71 @@
 mod builtins;
 
-use std::{collections::HashMap, time::Instant};
+use std::{fmt, time::Instant};
 
-use either::Either;
 use indent::indent_by;
 pub use inkwell::context::Context as LlvmContext;
 use inkwell::{
+    basic_block::BasicBlock,
     builder::Builder,
     memory_buffer::MemoryBuffer,
     module::Module,
-    types::BasicMetadataTypeEnum,
-    values::{
-        BasicMetadataValueEnum, BasicValue, BasicValueEnum, InstructionValue, IntValue,
-        PointerValue,
-    },
+    types::{BasicMetadataTypeEnum, BasicType, FunctionType, IntType},
+    values::{BasicMetadataValueEnum, BasicValueEnum, FunctionValue, IntValue, PointerValue},
     IntPredicate, OptimizationLevel,
 };
 use mini_c_ast::{
-    AssignmentExpression, BinaryExpression, BinaryOperator, Declaration, Expression,
+    AssignmentExpression, BinaryExpression, BinaryOperator, Declaration, Expression, ForStatement,
     FunctionCallExpression, FunctionDeclaration, Ident, IfStatement, Parameter, Program,
-    ReturnStatement, Scope, Statement, Type, Value, VariableDeclaration,
-    VariableReferenceExpression, WhileStatement,
+    ReturnStatement, Scope, Span, Spanned, Statement, Type, UnaryExpression, UnaryOperator, Value,
+    VariableDeclaration, VariableReferenceExpression, WhileStatement,
+};
+use mini_c_sema::{
+    cfg::ControlFlowGraph,
+    symbols::{builtin_functions, FunctionTable, PrototypeMismatch, Symbol, SymbolTable},
 };
 
 #[derive(Debug)]
 pub enum CompileTimeError {
     CallingUndefinedFunction(Ident),
-    AssigningToUndefinedVariable(Ident),
+    UsingUndefinedVariable(Ident),
     VariableAssignmentWithMismatchingType {
         ident: Ident,
         expected_type: Type,
         got_type: Type,
     },
+    IfConditionIsNotBool,
     WhileConditionIsNotBool,
+    ForConditionIsNotBool,
+    BreakOutsideOfLoop,
+    NegateOperandIsNotInt,
+    NotOperandIsNotBool,
+    LogicalOperandIsNotBool,
+    EqualityOperandsMismatch,
+    BinaryOperandIsNotInt(BinaryOperator),
+    ArrayIndexIsNotInt,
+    IndexingNonArrayVariable(Ident),
+    ArrayVariableUsedWithoutIndex(Ident),
     FunctionArgumentPassedWithMismatchingType {
         ident: Ident,
         argument_index: usize,
         expected_type: Type,
         got_type: Type,
     },
+    FunctionCalledWithWrongArgumentCount {
+        ident: Ident,
+        parameter_count: usize,
+        argument_count: usize,
+    },
     RedefinedFunction {
         previous: FunctionDeclaration,
         new: FunctionDeclaration,
     },
+    FunctionDefinitionWithDifferentReturnType(Ident),
+    FunctionDefinitionWithDifferentParameterCount(Ident),
+    FunctionDefinitionWithDifferentParameterType {
+        ident: Ident,
+        position: usize,
+    },
     RedefinedVariable {
         previous: VariableDeclaration,
         new: VariableDeclaration,
@@ -53,13 +76,193 @@ pub enum CompileTimeError {
     },
     VariableIsVoid(Ident),
     MissingReturnInNonMainNonVoidFunction(Ident),
+    ReturnWithValueInVoidFunction,
+    ReturnWithoutValueInNonVoidFunction,
+    ReturnWithMismatchingType {
+        expected_type: Type,
+        got_type: Type,
+    },
     PassingVoidAsFunctionArgument {
         function_ident: Ident,
         index: usize,
     },
 }
 
-type Result<T, E = CompileTimeError> = std::result::Result<T, E>;
+impl fmt::Display for CompileTimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CallingUndefinedFunction(ident) => {
+                write!(f, "Function {ident}() is not declared before use!")
+            }
+            Self::UsingUndefinedVariable(ident) => {
+                write!(f, "Variable {ident} is not declared before use!")
+            }
+            Self::VariableAssignmentWithMismatchingType { .. } => {
+                write!(
+                    f,
+                    "Variable and the assignment expression do not have the same type!"
+                )
+            }
+            Self::IfConditionIsNotBool => {
+                write!(
+                    f,
+                    "Conditional expression in if statement has non-bool type!"
+                )
+            }
+            Self::WhileConditionIsNotBool => {
+                write!(
+                    f,
+                    "Conditional expression in while statement has non-bool type!"
+                )
+            }
+            Self::ForConditionIsNotBool => {
+                write!(
+                    f,
+                    "Conditional expression in for statement has non-bool type!"
+                )
+            }
+            Self::BreakOutsideOfLoop => {
+                write!(
+                    f,
+                    "Break statement must appear inside a for/while statement!"
+                )
+            }
+            Self::NegateOperandIsNotInt => write!(f, "Negate \"-\" opcode must have int operand!"),
+            Self::NotOperandIsNotBool => write!(f, "Not \"!\" opcode must have bool operand!"),
+            Self::LogicalOperandIsNotBool => {
+                write!(f, "\"&&\"/\"||\" opcode must have bool operand!")
+            }
+            Self::EqualityOperandsMismatch => {
+                write!(
+                    f,
+                    "\"==\"/\"!=\" opcode must have same primitive type operand!"
+                )
+            }
+            Self::BinaryOperandIsNotInt(operator) => {
+                write!(f, "\"{operator}\" opcode must have int type operand!")
+            }
+            Self::ArrayIndexIsNotInt => write!(f, "Array index expressions must have int operand!"),
+            Self::IndexingNonArrayVariable(_) => write!(f, "Indexing an non-array variable!"),
+            Self::ArrayVariableUsedWithoutIndex(ident) => {
+                write!(f, "Array variable {ident} must be indexed before use!")
+            }
+            Self::FunctionArgumentPassedWithMismatchingType {
+                ident,
+                argument_index,
+                ..
+            } => {
+                write!(
+                    f,
+                    "Function {ident}() does not match the type of the call argument at \
+                     position {argument_index}!"
+                )
+            }
+            Self::FunctionCalledWithWrongArgumentCount {
+                ident,
+                parameter_count,
+                argument_count,
+            } => {
+                write!(
+                    f,
+                    "Function {ident}() is declared with {parameter_count} parameters but called \
+                     with {argument_count} arguments!"
+                )
+            }
+            Self::RedefinedFunction { new, .. } => {
+                write!(f, "Redefinition of function \"{}()\"!", new.ident().inner)
+            }
+            Self::FunctionDefinitionWithDifferentReturnType(ident) => {
+                write!(
+                    f,
+                    "Definition of function \"{ident}()\" with different return type!"
+                )
+            }
+            Self::FunctionDefinitionWithDifferentParameterCount(ident) => {
+                write!(
+                    f,
+                    "Definition of function \"{ident}()\" with different number of parameters!"
+                )
+            }
+            Self::FunctionDefinitionWithDifferentParameterType { ident, position } => {
+                write!(
+                    f,
+                    "Definition of function \"{ident}()\" with different parameter type at \
+                     position {position}!"
+                )
+            }
+            Self::RedefinedVariable { new, .. } => {
+                write!(
+                    f,
+                    "Redefinition of variable/parameter \"{}\" in the same scope!",
+                    new.ident.inner
+                )
+            }
+            Self::FunctionParameterIsVoid {
+                function_ident,
+                parameter_ident,
+            } => {
+                write!(
+                    f,
+                    "Parameter \"{parameter_ident}\" of function \"{function_ident}()\" has \
+                     void type!"
+                )
+            }
+            Self::VariableIsVoid(ident) => write!(f, "Variable \"{ident}\" has void type!"),
+            Self::MissingReturnInNonMainNonVoidFunction(ident) => {
+                write!(
+                    f,
+                    "The function \"{ident}()\" need to return a value at its end!"
+                )
+            }
+            Self::ReturnWithValueInVoidFunction => {
+                write!(
+                    f,
+                    "Function has void return type, but the return statement has a returned \
+                     expression!"
+                )
+            }
+            Self::ReturnWithoutValueInNonVoidFunction => {
+                write!(
+                    f,
+                    "Function has non-void return type, but the return statement has no returned \
+                     expression!"
+                )
+            }
+            Self::ReturnWithMismatchingType {
+                expected_type,
+                got_type,
+            } => {
+                write!(
+                    f,
+                    "Function has return type \"{expected_type}\", but the returned expression \
+                     has type \"{got_type}\"!"
+                )
+            }
+            Self::PassingVoidAsFunctionArgument {
+                function_ident,
+                index,
+            } => {
+                write!(
+                    f,
+                    "Function {function_ident}() receives a void call argument at position \
+                     {index}!"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for CompileTimeError {}
+
+impl CompileTimeError {
+    /// Attach the span of the construct that caused the error.
+    fn at(self, span: Span) -> Spanned<Self> {
+        Spanned::new(self, span)
+    }
+}
+
+/// Errors point at the code that caused them, like the ones of the semantic analysis.
+type Result<T, E = Spanned<CompileTimeError>> = std::result::Result<T, E>;
 
 #[must_use]
 pub struct Compiler {
@@ -68,8 +271,13 @@ pub struct Compiler {
     module: Module<'static>,
     builder: Builder<'static>,
     // Our storage for LLVM stuff
-    vars: HashMap<Ident, PointerValue<'static>>,
     function_block_counter: usize,
+    // The function whose body is being compiled
+    current_function: Option<FunctionValue<'static>>,
+    // The return type of `current_function`
+    current_return_type: Type,
+    // Exit blocks of the loops we're currently inside of, innermost last
+    loop_end_blocks: Vec<BasicBlock<'static>>,
     // Our type system
     type_system: TypeSystem,
 }
@@ -90,46 +298,69 @@ impl Compiler {
             context,
             builder: context.create_builder(),
             module,
-            vars: HashMap::new(),
             function_block_counter: 0,
+            current_function: None,
+            current_return_type: Type::Void,
+            loop_end_blocks: vec![],
             type_system: TypeSystem::new(),
         }
     }
 
     pub fn compile(&mut self, root: &Program) -> Result<()> {
-        // Register the `print` function into the type system
-        self.type_system
-            .declare_builtin_function(
-                Type::Void,
-                "print".into(),
-                vec![Parameter(Type::Int, "input".into())],
-            )
-            .unwrap();
-        self.type_system
-            .declare_builtin_function(
-                Type::Void,
-                "println".into(),
-                vec![Parameter(Type::Int, "input".into())],
-            )
-            .unwrap();
+        // Register the builtins into the type system, their code is already in the module
+        for builtin in builtin_functions(root.preamble) {
+            self.type_system
+                .declare_function(&builtin)
+                .expect("builtins have different names");
+        }
+
+        if root.preamble {
+            self.compile_minicio_builtins();
+        }
+
+        // The outermost scope holds the global variables
+        self.type_system.scopes.push_scope();
+
+        // Declare all functions first, so they can be called before their definition
+        for decl in &root.declarations {
+            if let Declaration::Function(function) = decl {
+                self.declare_function(function)?;
+            }
+        }
 
         for decl in &root.declarations {
             self.compile_declaration(decl)?;
         }
 
+        self.type_system.scopes.pop_scope();
+
         Ok(())
     }
 
+    /// Link in the functions provided by `#include "minicio.h"`.
+    fn compile_minicio_builtins(&mut self) {
+        let ir = builtins::MINICIO_IR;
+        let ir = MemoryBuffer::create_from_memory_range_copy(ir.as_bytes(), "minicio");
+
+        let minicio_module = self
+            .context
+            .create_module_from_ir(ir)
+            .expect("Failed to load minicio builtins from LLVM IR");
+        self.module
+            .link_in_module(minicio_module)
+            .expect("Failed to link minicio builtins");
+    }
+
     pub fn run(&self) {
         println!("--------- Generated LLVM IR: ---------");
 
-        println!("{}", self.module.print_to_string().to_string());
+        println!("{}", self.ir());
         self.module.print_to_file("llvm-ir.ll").unwrap();
 
         println!("-------------------------------------------");
 
-        if let Err(msg) = self.module.verify() {
-            eprintln!("LLVM IR check failed: {}", indent_by(8, msg.to_string()))
+        if let Err(msg) = self.verify() {
+            eprintln!("LLVM IR check failed: {}", indent_by(8, msg))
         }
 
         let engine = self
@@ -151,51 +382,76 @@ impl Compiler {
         println!("elapsed = {:?}", instant.elapsed());
     }
 
+    /// The generated LLVM IR, in its textual form.
+    pub fn ir(&self) -> String {
+        self.module.print_to_string().to_string()
+    }
+
+    /// Run LLVM's checks on the generated IR, like every block ending in a single terminator.
+    pub fn verify(&self) -> Result<(), String> {
+        self.module.verify().map_err(|msg| msg.to_string())
+    }
+
     fn compile_declaration(&mut self, declaration: &Declaration) -> Result<()> {
         match declaration {
             Declaration::Variable(decls) => {
-                decls.iter().try_for_each(|decl| {
-                    self.compile_variable_declaration(decl.clone()).map(|_| ())
-                })
+                decls
+                    .iter()
+                    .try_for_each(|decl| self.compile_global_variable_declaration(decl.clone()))
             }
-            Declaration::Function(function) => self.compile_function(function),
+            Declaration::Function(function) => {
+                match function {
+                    FunctionDeclaration(.., Some(scope)) => self.compile_function(function, scope),
+                    // Prototypes were already handled by `declare_function`
+                    FunctionDeclaration(.., None) => Ok(()),
+                }
+            }
+            Declaration::Error => unreachable!("programs with syntax errors aren't compiled"),
         }
     }
 
-    fn compile_function(&mut self, function: &FunctionDeclaration) -> Result<()> {
-        self.function_block_counter = 0;
+    /// Register a function signature in the type system and in the LLVM module, without
+    /// compiling its body.
+    fn declare_function(&mut self, function: &FunctionDeclaration) -> Result<()> {
         self.type_system.declare_function(function)?;
-        self.type_system.scopes.push_scope();
 
-        let FunctionDeclaration(return_type, function_ident, parameters, scope) = function;
-
-        let function = {
-            let parameter_types = &parameters
-                .iter()
-                .map(|Parameter(param_type, param_ident)| {
-                    let typ = match param_type {
-                        Type::Void => {
-                            return Err(CompileTimeError::FunctionParameterIsVoid {
-                                function_ident: function_ident.clone(),
-                                parameter_ident: param_ident.clone(),
-                            });
-                        }
-                        Type::Int => self.context.i64_type(),
-                        Type::Bool => self.context.bool_type(),
+        let FunctionDeclaration(return_type, function_ident, parameters, _) = function;
+
+        let parameter_types = &parameters
+            .iter()
+            .map(|Parameter(param_type, param_ident)| {
+                let Some(typ) = self.llvm_type(*param_type) else {
+                    let error = CompileTimeError::FunctionParameterIsVoid {
+                        function_ident: function_ident.inner.clone(),
+                        parameter_ident: param_ident.inner.clone(),
                     };
-                    Ok(BasicMetadataTypeEnum::IntType(typ))
-                })
-                .collect::<Result<Vec<_>>>()?;
+                    return Err(error.at(param_ident.span));
+                };
+                Ok(BasicMetadataTypeEnum::IntType(typ))
+            })
+            .collect::<Result<Vec<_>>>()?;
 
-            let function_type = match return_type {
-                Type::Void => self.context.void_type().fn_type(parameter_types, false),
-                Type::Int => self.context.i64_type().fn_type(parameter_types, false),
-                Type::Bool => self.context.bool_type().fn_type(parameter_types, false),
-            };
+        let function_type = self.llvm_function_type(*return_type, parameter_types);
 
+        // A prototype and its definition share the same LLVM function
+        if self.module.get_function(&function_ident.inner).is_none() {
             self.module
-                .add_function(function_ident, function_type, None)
-        };
+                .add_function(&function_ident.inner, function_type, None);
+        }
+
+        Ok(())
+    }
+
+    fn compile_function(&mut self, function: &FunctionDeclaration, scope: &Scope) -> Result<()> {
+        self.function_block_counter = 0;
+        self.type_system.scopes.push_scope();
+
+        let FunctionDeclaration(return_type, function_ident, parameters, _) = function;
+
+        // Unwrap safety: all functions were declared before compiling any of them
+        let function = self.module.get_function(&function_ident.inner).unwrap();
+        self.current_function = Some(function);
+        self.current_return_type = *return_type;
 
         let function_block = self.context.append_basic_block(function, "entry");
         self.builder.position_at_end(function_block);
@@ -212,28 +468,31 @@ impl Compiler {
 
             let pointer = self.compile_variable_declaration(variable_declaration)?;
 
-            self.builder.build_store(pointer, llvm_param);
+            let value = LlvmExpr::with_type(typ, Some(llvm_param.into_int_value()));
+            self.build_typed_store(pointer, value);
         }
-        self.compile_scope(scope)?;
-
-        let Scope(_, statements) = scope;
+        self.compile_scope_body(scope)?;
 
-        let is_last_statement_return = matches!(statements.last(), Some(Statement::Return(..)));
-        if !is_last_statement_return {
+        if ControlFlowGraph::of_function(scope).falls_off_end() {
             let is_return_type_void = matches!(return_type, Type::Void);
 
-            match (is_return_type_void, function_ident.as_str()) {
+            match (is_return_type_void, function_ident.inner.as_str()) {
                 (true, _) => drop(self.builder.build_return(None)),
                 (false, "main") => {
-                    self.builder
-                        .build_return(Some(&self.context.i64_type().const_int(0, false)));
+                    let zero = self.llvm_type(*return_type).unwrap().const_zero();
+                    self.builder.build_return(Some(&zero));
                 }
                 (false, _) => {
-                    return Err(CompileTimeError::MissingReturnInNonMainNonVoidFunction(
-                        function_ident.to_string(),
-                    ));
+                    let error = CompileTimeError::MissingReturnInNonMainNonVoidFunction(
+                        function_ident.inner.to_string(),
+                    );
+                    return Err(error.at(function_ident.span));
                 }
             }
+        } else if !self.is_current_block_terminated() {
+            // Every path returned before the end, like in an `if` with returns in both branches,
+            // but the block where they join still needs a terminator
+            self.builder.build_unreachable();
         }
 
         self.type_system.scopes.pop_scope();
@@ -241,33 +500,88 @@ impl Compiler {
         Ok(())
     }
 
-    fn compile_return(&mut self, statement: &ReturnStatement) -> Result<()> {
+    fn compile_return(&mut self, statement: &ReturnStatement, span: Span) -> Result<()> {
         let ReturnStatement(expression) = statement;
 
-        let expr = expression
+        let value = expression
             .as_ref()
-            .map(|expr| self.compile_expression(expr).map(LlvmExpr::to_value))
-            .transpose()?
-            .flatten();
+            .map(|expression| {
+                self.compile_expression(expression)
+                    .map(|value| (value, expression.span))
+            })
+            .transpose()?;
 
-        let expr = expr.as_ref().map(|x| x as &dyn BasicValue);
+        match (self.current_return_type, value) {
+            (Type::Void, None) => drop(self.builder.build_return(None)),
+            (Type::Void, Some((_, value_span))) => {
+                return Err(CompileTimeError::ReturnWithValueInVoidFunction.at(value_span));
+            }
+            (_, None) => {
+                return Err(CompileTimeError::ReturnWithoutValueInNonVoidFunction.at(span));
+            }
+            (expected_type, Some((value, value_span))) => {
+                let got_type = value.to_type();
+                if got_type != expected_type {
+                    let error = CompileTimeError::ReturnWithMismatchingType {
+                        expected_type,
+                        got_type,
+                    };
+                    return Err(error.at(value_span));
+                }
+
+                // `value` isn't void, it has the type of a non-void function
+                self.builder.build_return(Some(&value.to_value().unwrap()));
+            }
+        }
 
-        self.builder.build_return(expr);
         Ok(())
     }
 
+    /// If the block that the builder is at already ended, with a `return` or a `break`.
+    fn is_current_block_terminated(&self) -> bool {
+        self.builder
+            .get_insert_block()
+            .and_then(|block| block.get_terminator())
+            .is_some()
+    }
+
+    /// Jump to `block` at the end of a branch or loop body, unless it already jumped elsewhere.
+    fn build_branch_if_unterminated(&self, block: BasicBlock<'static>) {
+        if !self.is_current_block_terminated() {
+            self.builder.build_unconditional_branch(block);
+        }
+    }
+
     fn compile_scope(&mut self, scope: &Scope) -> Result<()> {
-        let Scope(variable_declarations, statements) = scope;
         self.type_system.scopes.push_scope();
+        self.compile_scope_body(scope)?;
+        self.type_system.scopes.pop_scope();
+        Ok(())
+    }
+
+    /// Compile the contents of a scope without pushing a new one, used for function bodies,
+    /// which share the scope of the parameters.
+    fn compile_scope_body(&mut self, scope: &Scope) -> Result<()> {
+        let Scope(variable_declarations, statements) = scope;
 
         for decl in variable_declarations {
             self.compile_variable_declaration(decl.clone())?;
         }
         for statement in statements {
+            // Statements after a `return` or `break` are unreachable, but still need a block to
+            // live in, LLVM doesn't accept instructions after a terminator
+            if self.is_current_block_terminated() {
+                let current_function = self.current_function.unwrap();
+                let unreachable_block = self.context.append_basic_block(
+                    current_function,
+                    self.new_block_name("unreachable").as_str(),
+                );
+                self.builder.position_at_end(unreachable_block);
+            }
+
             self.compile_statement(statement)?;
         }
 
-        self.type_system.scopes.pop_scope();
         Ok(())
     }
 
@@ -275,39 +589,101 @@ impl Compiler {
         &mut self,
         declaration: VariableDeclaration,
     ) -> Result<PointerValue<'static>> {
-        if declaration.array_len.is_some() {
-            unimplemented!("We didn't implemented arrays compilation and handling yet");
+        let Some(element_type) = self.llvm_type(declaration.typ) else {
+            let error = CompileTimeError::VariableIsVoid(declaration.ident.inner.clone());
+            return Err(error.at(declaration.ident.span));
+        };
+
+        let alloca_pointer = match declaration.array_len {
+            None => self.build_entry_alloca(element_type, &declaration.ident.inner),
+            Some(array_len) => {
+                self.build_entry_alloca(
+                    element_type.array_type(array_len as u32),
+                    &declaration.ident.inner,
+                )
+            }
+        };
+
+        self.type_system
+            .declare_variable(declaration, alloca_pointer)?;
+
+        Ok(alloca_pointer)
+    }
+
+    /// Allocate a local in the entry block of the current function, wherever it's declared.
+    ///
+    /// Allocas inside loops would grow the stack in every iteration, and LLVM only promotes the
+    /// ones in the entry block to registers.
+    fn build_entry_alloca(
+        &self,
+        typ: impl BasicType<'static>,
+        name: &str,
+    ) -> PointerValue<'static> {
+        // Unwrap safety: the entry block is created before compiling the function body
+        let entry_block = self
+            .current_function
+            .unwrap()
+            .get_first_basic_block()
+            .unwrap();
+
+        // Keep allocas before the other instructions, like the stores of the parameters
+        let entry_builder = self.context.create_builder();
+        match entry_block.get_first_instruction() {
+            Some(first_instruction) => entry_builder.position_before(&first_instruction),
+            None => entry_builder.position_at_end(entry_block),
         }
 
-        let alloca_pointer = match declaration.typ {
-            Type::Void => return Err(CompileTimeError::VariableIsVoid(declaration.ident.clone())),
-            Type::Int => {
-                self.builder
-                    .build_alloca(self.context.i64_type(), &declaration.ident)
+        entry_builder.build_alloca(typ, name)
+    }
+
+    /// Globals live in the module instead of a function's stack, and start zeroed.
+    fn compile_global_variable_declaration(
+        &mut self,
+        declaration: VariableDeclaration,
+    ) -> Result<()> {
+        let Some(element_type) = self.llvm_type(declaration.typ) else {
+            let error = CompileTimeError::VariableIsVoid(declaration.ident.inner.clone());
+            return Err(error.at(declaration.ident.span));
+        };
+
+        let global = match declaration.array_len {
+            None => {
+                let global = self
+                    .module
+                    .add_global(element_type, None, &declaration.ident.inner);
+                global.set_initializer(&element_type.const_zero());
+                global
             }
-            Type::Bool => {
-                self.builder
-                    .build_alloca(self.context.bool_type(), &declaration.ident)
+            Some(array_len) => {
+                let array_type = element_type.array_type(array_len as u32);
+                let global = self
+                    .module
+                    .add_global(array_type, None, &declaration.ident.inner);
+                global.set_initializer(&array_type.const_zero());
+                global
             }
         };
 
-        self.vars.insert(declaration.ident.clone(), alloca_pointer);
-        self.type_system.scopes.try_declare_variable(declaration)?;
+        self.type_system
+            .declare_variable(declaration, global.as_pointer_value())?;
 
-        Ok(alloca_pointer)
+        Ok(())
     }
 
-    fn compile_statement(&mut self, statement: &Statement) -> Result<()> {
-        match statement {
+    fn compile_statement(&mut self, statement: &Spanned<Statement>) -> Result<()> {
+        match &statement.inner {
             Statement::If(statement) => self.compile_if(statement)?,
-            Statement::For(_statement) => todo!(),
+            Statement::For(statement) => self.compile_for(statement)?,
             Statement::While(statement) => self.compile_while(statement)?,
-            Statement::Break => todo!(),
-            Statement::Return(statement) => self.compile_return(statement)?,
+            Statement::Break => self.compile_break(statement.span)?,
+            Statement::Return(return_statement) => {
+                self.compile_return(return_statement, statement.span)?
+            }
             Statement::Scope(scope) => self.compile_scope(scope)?,
             Statement::Expression(expression) => {
                 self.compile_expression(expression)?;
             }
+            Statement::Error => unreachable!("programs with syntax errors aren't compiled"),
         }
 
         Ok(())
@@ -316,40 +692,41 @@ impl Compiler {
     fn compile_if(&mut self, statement: &IfStatement) -> Result<()> {
         let IfStatement(condition, then_scope, else_scope) = statement;
 
-        let LlvmExpr::Bool(condition) = self.compile_expression(condition)? else {
-            return Err(CompileTimeError::WhileConditionIsNotBool);
+        let LlvmExpr::Bool(condition_value) = self.compile_expression(condition)? else {
+            return Err(CompileTimeError::IfConditionIsNotBool.at(condition.span));
         };
 
-        let last_function = self.module.get_last_function().unwrap();
+        let current_function = self.current_function.unwrap();
         let [if_then_block, if_end_block] = ["if_then", "if_end"].map(|name| {
             self.context
-                .append_basic_block(last_function, self.new_block_name(name).as_str())
+                .append_basic_block(current_function, self.new_block_name(name).as_str())
         });
 
         match else_scope {
             None => {
                 self.builder
-                    .build_conditional_branch(condition, if_then_block, if_end_block);
+                    .build_conditional_branch(condition_value, if_then_block, if_end_block);
             }
             Some(else_scope) => {
                 let if_else_block = self
                     .context
-                    .append_basic_block(last_function, self.new_block_name("if_else").as_str());
+                    .append_basic_block(current_function, self.new_block_name("if_else").as_str());
 
-                self.builder
-                    .build_conditional_branch(condition, if_then_block, if_else_block);
+                self.builder.build_conditional_branch(
+                    condition_value,
+                    if_then_block,
+                    if_else_block,
+                );
 
                 self.builder.position_at_end(if_else_block);
                 self.compile_scope(else_scope)?;
-                self.builder.build_unconditional_branch(if_end_block);
+                self.build_branch_if_unterminated(if_end_block);
             }
         }
 
         self.builder.position_at_end(if_then_block);
         self.compile_scope(then_scope)?;
-        if if_then_block.get_terminator().is_none() {
-            self.builder.build_unconditional_branch(if_end_block);
-        }
+        self.build_branch_if_unterminated(if_end_block);
 
         self.builder.position_at_end(if_end_block);
 
@@ -359,11 +736,11 @@ impl Compiler {
     fn compile_while(&mut self, statement: &WhileStatement) -> Result<()> {
         let WhileStatement(condition, scope) = &statement;
 
-        let last_function = self.module.get_last_function().unwrap();
+        let current_function = self.current_function.unwrap();
         let [while_condition_block, while_scope_block, while_end_block] =
             ["while_condition", "while_scope", "while_end"].map(|name| {
                 self.context
-                    .append_basic_block(last_function, self.new_block_name(name).as_str())
+                    .append_basic_block(current_function, self.new_block_name(name).as_str())
             });
 
         // Build while entrance
@@ -373,36 +750,104 @@ impl Compiler {
         // Build while condition
         self.builder.position_at_end(while_condition_block);
 
-        let condition = condition
-            .as_ref()
-            .unwrap_or(&Expression::Value(Value::Bool(true)));
+        // A missing condition is always true
+        let always_true = Spanned::new(Expression::Value(Value::Bool(true)), Span::default());
+        let condition = condition.as_ref().unwrap_or(&always_true);
 
-        let LlvmExpr::Bool(condition) = self.compile_expression(condition)? else {
-            return Err(CompileTimeError::WhileConditionIsNotBool);
+        let LlvmExpr::Bool(condition_value) = self.compile_expression(condition)? else {
+            return Err(CompileTimeError::WhileConditionIsNotBool.at(condition.span));
         };
 
         self.builder
-            .build_conditional_branch(condition, while_scope_block, while_end_block);
+            .build_conditional_branch(condition_value, while_scope_block, while_end_block);
 
         // Build while scope
         self.builder.position_at_end(while_scope_block);
 
+        self.loop_end_blocks.push(while_end_block);
         self.compile_scope(scope)?;
+        self.loop_end_blocks.pop();
 
-        self.builder
-            .build_unconditional_branch(while_condition_block);
+        self.build_branch_if_unterminated(while_condition_block);
         self.builder.position_at_end(while_end_block);
 
         Ok(())
     }
 
-    fn compile_expression(&mut self, expression: &Expression) -> Result<LlvmExpr> {
-        let value = match expression {
+    fn compile_for(&mut self, statement: &ForStatement) -> Result<()> {
+        let ForStatement((initialization, condition, step), scope) = &statement;
+
+        let current_function = self.current_function.unwrap();
+        let [for_condition_block, for_scope_block, for_step_block, for_end_block] =
+            ["for_condition", "for_scope", "for_step", "for_end"].map(|name| {
+                self.context
+                    .append_basic_block(current_function, self.new_block_name(name).as_str())
+            });
+
+        // Build for initialization
+        if let Some(initialization) = initialization {
+            self.compile_expression(initialization)?;
+        }
+
+        // Build for entrance
+        self.builder.build_unconditional_branch(for_condition_block);
+
+        // Build for condition
+        self.builder.position_at_end(for_condition_block);
+
+        // A missing condition is always true
+        let always_true = Spanned::new(Expression::Value(Value::Bool(true)), Span::default());
+        let condition = condition.as_ref().unwrap_or(&always_true);
+
+        let LlvmExpr::Bool(condition_value) = self.compile_expression(condition)? else {
+            return Err(CompileTimeError::ForConditionIsNotBool.at(condition.span));
+        };
+
+        self.builder
+            .build_conditional_branch(condition_value, for_scope_block, for_end_block);
+
+        // Build for scope
+        self.builder.position_at_end(for_scope_block);
+
+        self.loop_end_blocks.push(for_end_block);
+        self.compile_scope(scope)?;
+        self.loop_end_blocks.pop();
+
+        self.build_branch_if_unterminated(for_step_block);
+
+        // Build for step
+        self.builder.position_at_end(for_step_block);
+
+        if let Some(step) = step {
+            self.compile_expression(step)?;
+        }
+
+        self.builder.build_unconditional_branch(for_condition_block);
+        self.builder.position_at_end(for_end_block);
+
+        Ok(())
+    }
+
+    fn compile_break(&mut self, span: Span) -> Result<()> {
+        let Some(&loop_end_block) = self.loop_end_blocks.last() else {
+            return Err(CompileTimeError::BreakOutsideOfLoop.at(span));
+        };
+
+        self.builder.build_unconditional_branch(loop_end_block);
+        Ok(())
+    }
+
+    fn compile_expression(&mut self, expression: &Spanned<Expression>) -> Result<LlvmExpr> {
+        let span = expression.span;
+
+        let value = match &expression.inner {
             Expression::Value(value) => self.compile_expression_value(value)?,
-            Expression::Assignment(assignment) => self.compile_expression_assignment(assignment)?,
-            Expression::Binary(expression) => self.compile_expression_binary(expression)?,
-            Expression::Unary(_ /*Box<UnaryExpression>*/) => todo!(),
-            Expression::FunctionCall(call) => self.compile_expression_function_call(call)?,
+            Expression::Assignment(assignment) => {
+                self.compile_expression_assignment(assignment, span)?
+            }
+            Expression::Binary(expression) => self.compile_expression_binary(expression, span)?,
+            Expression::Unary(expression) => self.compile_expression_unary(expression, span)?,
+            Expression::FunctionCall(call) => self.compile_expression_function_call(call, span)?,
             Expression::VariableReference(variable_reference) => {
                 self.compile_expression_variable_reference(variable_reference)?
             }
@@ -413,9 +858,13 @@ impl Compiler {
 
     fn compile_expression_value(&mut self, value: &Value) -> Result<LlvmExpr> {
         Ok(match value {
-            Value::Int(int) => LlvmExpr::Int(self.context.i64_type().const_int(*int as u64, false)),
+            Value::Int(int) => {
+                let int_type = self.llvm_type(Type::Int).unwrap();
+                LlvmExpr::Int(int_type.const_int(*int as u64, false))
+            }
             Value::Bool(boo) => {
-                LlvmExpr::Bool(self.context.bool_type().const_int(*boo as u64, false))
+                let bool_type = self.llvm_type(Type::Bool).unwrap();
+                LlvmExpr::Bool(bool_type.const_int(*boo as u64, false))
             }
         })
     }
@@ -423,55 +872,56 @@ impl Compiler {
     fn compile_expression_assignment(
         &mut self,
         assignment: &AssignmentExpression,
+        span: Span,
     ) -> Result<LlvmExpr> {
         let AssignmentExpression(variable_reference, expression) = assignment;
 
         let value = self.compile_expression(expression)?;
 
-        let var_ident = match variable_reference {
-            VariableReferenceExpression::Normal(ident) => ident,
-            _ => todo!("Arrays are not supportedk"),
-        };
+        let (var_pointer, expected_type) = self.compile_variable_pointer(variable_reference)?;
 
         let got_type = value.to_type();
-        let VariableDeclaration {
-            typ: expected_type, ..
-        } = self
-            .type_system
-            .scopes
-            .get_variable(var_ident)
-            .ok_or_else(|| CompileTimeError::AssigningToUndefinedVariable(var_ident.to_string()))?;
-
-        if got_type != *expected_type {
-            return Err(CompileTimeError::VariableAssignmentWithMismatchingType {
-                ident: var_ident.to_string(),
-                expected_type: *expected_type,
+        if got_type != expected_type {
+            let error = CompileTimeError::VariableAssignmentWithMismatchingType {
+                ident: variable_reference.ident().inner.to_string(),
+                expected_type,
                 got_type,
-            });
+            };
+            return Err(error.at(span));
         }
 
-        // Index safety: checked above that the variable exists in the typesystem
-        let var_pointer = self.vars[var_ident];
-
-        // Unwrap safety: we checked that `value` is not void by comparing it's type to a
-        // variable type (and variables can't be void)
-        self.builder
-            .build_store(var_pointer, value.to_value().unwrap());
+        // `value` isn't void, we checked that it has the type of a variable (and variables can't
+        // be void)
+        self.build_typed_store(var_pointer, value);
         Ok(value)
     }
 
-    fn compile_expression_binary(&mut self, expression: &BinaryExpression) -> Result<LlvmExpr> {
+    fn compile_expression_binary(
+        &mut self,
+        expression: &BinaryExpression,
+        span: Span,
+    ) -> Result<LlvmExpr> {
         let BinaryExpression(lhs, operator, rhs) = expression;
 
+        if let BinaryOperator::And | BinaryOperator::Or = operator {
+            return self.compile_expression_logical(lhs, operator, rhs, span);
+        }
+
         let lhs = self.compile_expression(lhs)?;
         let rhs = self.compile_expression(rhs)?;
 
-        if lhs.to_type() != rhs.to_type() {
-            todo!("types mismatch, return error here");
-        }
-
-        let lhs = lhs.to_value().unwrap();
-        let rhs = rhs.to_value().unwrap();
+        // `==` and `!=` compare any two values of the same type, bools are compared as `i1`s,
+        // the other operators only take ints
+        let is_equality = matches!(operator, BinaryOperator::Equals | BinaryOperator::NotEquals);
+        let (lhs, rhs) = match (lhs, rhs) {
+            (LlvmExpr::Int(lhs), LlvmExpr::Int(rhs)) => (lhs, rhs),
+            (LlvmExpr::Bool(lhs), LlvmExpr::Bool(rhs)) if is_equality => (lhs, rhs),
+            _ if is_equality => return Err(CompileTimeError::EqualityOperandsMismatch.at(span)),
+            _ => {
+                let error = CompileTimeError::BinaryOperandIsNotInt(operator.clone());
+                return Err(error.at(span));
+            }
+        };
 
         let operation_value = match operator {
             BinaryOperator::Add => self.builder.build_int_add(lhs, rhs, ""),
@@ -479,8 +929,6 @@ impl Compiler {
             BinaryOperator::Mul => self.builder.build_int_mul(lhs, rhs, ""),
             BinaryOperator::Div => self.builder.build_int_signed_div(lhs, rhs, ""),
             BinaryOperator::Modulo => self.builder.build_int_signed_rem(lhs, rhs, ""),
-            // And => self.builder.build_int_xxxxx(lhs, rhs, ""),
-            // Or => self.builder.build_int_xxxxx(lhs, rhs, ""),
             BinaryOperator::Equals => {
                 self.builder
                     .build_int_compare(IntPredicate::EQ, lhs, rhs, "")
@@ -505,89 +953,255 @@ impl Compiler {
                 self.builder
                     .build_int_compare(IntPredicate::SLE, lhs, rhs, "")
             }
-            op => todo!("Implement binary operation for {op:?}"),
+            BinaryOperator::And | BinaryOperator::Or => unreachable!("handled above"),
         };
 
         Ok(LlvmExpr::from(operation_value))
     }
 
+    /// Compiles `&&` and `||` with short-circuit evaluation, the right-hand side is only
+    /// evaluated if the left-hand side doesn't decide the result.
+    fn compile_expression_logical(
+        &mut self,
+        lhs: &Spanned<Expression>,
+        operator: &BinaryOperator,
+        rhs: &Spanned<Expression>,
+        span: Span,
+    ) -> Result<LlvmExpr> {
+        let LlvmExpr::Bool(lhs) = self.compile_expression(lhs)? else {
+            return Err(CompileTimeError::LogicalOperandIsNotBool.at(span));
+        };
+
+        let current_function = self.current_function.unwrap();
+        let [logical_rhs_block, logical_end_block] = ["logical_rhs", "logical_end"].map(|name| {
+            self.context
+                .append_basic_block(current_function, self.new_block_name(name).as_str())
+        });
+
+        // The value of the whole expression when the right-hand side is skipped
+        let lhs_block = self.builder.get_insert_block().unwrap();
+        let short_circuit_value = match operator {
+            BinaryOperator::And => {
+                self.builder
+                    .build_conditional_branch(lhs, logical_rhs_block, logical_end_block);
+                self.context.bool_type().const_zero()
+            }
+            BinaryOperator::Or => {
+                self.builder
+                    .build_conditional_branch(lhs, logical_end_block, logical_rhs_block);
+                self.context.bool_type().const_all_ones()
+            }
+            _ => unreachable!("only called for logical operators"),
+        };
+
+        self.builder.position_at_end(logical_rhs_block);
+        let LlvmExpr::Bool(rhs) = self.compile_expression(rhs)? else {
+            return Err(CompileTimeError::LogicalOperandIsNotBool.at(span));
+        };
+        // The right-hand side might have created blocks too, the phi needs the last one
+        let rhs_block = self.builder.get_insert_block().unwrap();
+        self.builder.build_unconditional_branch(logical_end_block);
+
+        self.builder.position_at_end(logical_end_block);
+        let phi = self.builder.build_phi(self.context.bool_type(), "");
+        phi.add_incoming(&[(&short_circuit_value, lhs_block), (&rhs, rhs_block)]);
+
+        Ok(LlvmExpr::Bool(phi.as_basic_value().into_int_value()))
+    }
+
+    fn compile_expression_unary(
+        &mut self,
+        expression: &UnaryExpression,
+        span: Span,
+    ) -> Result<LlvmExpr> {
+        let UnaryExpression(operator, operand) = expression;
+
+        let operand = self.compile_expression(operand)?;
+
+        match (operator, operand) {
+            (UnaryOperator::Negative, LlvmExpr::Int(value)) => {
+                Ok(LlvmExpr::Int(self.builder.build_int_neg(value, "")))
+            }
+            (UnaryOperator::Negative, _) => Err(CompileTimeError::NegateOperandIsNotInt.at(span)),
+            (UnaryOperator::Not, LlvmExpr::Bool(value)) => {
+                Ok(LlvmExpr::Bool(self.builder.build_not(value, "")))
+            }
+            (UnaryOperator::Not, _) => Err(CompileTimeError::NotOperandIsNotBool.at(span)),
+        }
+    }
+
     fn compile_expression_function_call(
         &mut self,
         call: &FunctionCallExpression,
+        span: Span,
     ) -> Result<LlvmExpr> {
         let FunctionCallExpression(function_ident, arguments) = call;
-
-        let Some(function) = self.module.get_function(function_ident) else {
-            return Err(CompileTimeError::CallingUndefinedFunction(
-                function_ident.to_string(),
-            ));
+        let Spanned {
+            inner: function_ident,
+            span: ident_span,
+        } = function_ident;
+
+        // The module also has functions that mini-c programs can't call, like `printf`
+        let Some(FunctionDeclaration(return_type, _, parameters, _)) =
+            self.type_system.functions.get(function_ident).cloned()
+        else {
+            let error = CompileTimeError::CallingUndefinedFunction(function_ident.to_string());
+            return Err(error.at(*ident_span));
         };
 
-        let arguments = arguments
+        // Unwrap safety: every function in the type system was added to the module
+        let function = self.module.get_function(function_ident).unwrap();
+
+        // LLVM would build a call with the wrong number of arguments, and only complain later
+        if parameters.len() != arguments.len() {
+            let error = CompileTimeError::FunctionCalledWithWrongArgumentCount {
+                ident: function_ident.to_string(),
+                parameter_count: parameters.len(),
+                argument_count: arguments.len(),
+            };
+            return Err(error.at(span));
+        }
+
+        let argument_values = arguments
             .iter()
             .enumerate()
             .map(|(index, argument)| {
-                self.compile_expression(argument).and_then(|value| {
-                    value.to_metadata_value_enum().ok_or_else(|| {
-                        CompileTimeError::PassingVoidAsFunctionArgument {
+                let value = self.compile_expression(argument)?;
+                match value {
+                    LlvmExpr::Void => {
+                        let error = CompileTimeError::PassingVoidAsFunctionArgument {
                             function_ident: function_ident.to_string(),
                             index,
-                        }
-                    })
-                })
+                        };
+                        Err(error.at(argument.span))
+                    }
+                    value => Ok(value),
+                }
             })
             .collect::<Result<Vec<_>>>()?;
 
-        let FunctionDeclaration(_, _, parameters, _) = &self
-            .type_system
-            .functions
-            .get(function_ident)
-            .expect(&format!(
-                "Undefined function {function_ident}, is it builtin?"
-            ));
-
         parameters
             .iter()
             .map(|Parameter(param_type, _)| param_type)
-            .zip(&arguments)
+            .zip(arguments.iter().zip(&argument_values))
             .enumerate()
-            .try_for_each(|(argument_index, (param_type, argument_type))| {
-                let argument_type = LlvmExpr::from(*argument_type).to_type();
+            .try_for_each(|(argument_index, (param_type, (argument, value)))| {
+                let argument_type = value.to_type();
 
                 (*param_type == argument_type).then_some(()).ok_or_else(|| {
-                    CompileTimeError::FunctionArgumentPassedWithMismatchingType {
+                    let error = CompileTimeError::FunctionArgumentPassedWithMismatchingType {
                         ident: function_ident.to_string(),
                         argument_index,
                         expected_type: *param_type,
                         got_type: argument_type,
-                    }
+                    };
+                    error.at(argument.span)
                 })
             })?;
 
-        let call_site = self
+        // Unwrap safety: void arguments were rejected above
+        let arguments = argument_values
+            .into_iter()
+            .map(|argument| argument.to_metadata_value_enum().unwrap())
+            .collect::<Vec<_>>();
+
+        let return_value = self
             .builder
             .build_call(function, arguments.as_slice(), "")
-            .try_as_basic_value();
+            .try_as_basic_value()
+            .left()
+            .map(BasicValueEnum::into_int_value);
 
-        Ok(LlvmExpr::from(call_site))
+        Ok(LlvmExpr::with_type(return_type, return_value))
     }
 
     fn compile_expression_variable_reference(
         &mut self,
         variable_reference: &VariableReferenceExpression,
     ) -> Result<LlvmExpr> {
-        let ident = match variable_reference {
-            VariableReferenceExpression::Normal(ident) => ident,
-            _ => todo!("Arrays are not supported"),
-        };
+        let (var_pointer, typ) = self.compile_variable_pointer(variable_reference)?;
 
-        let var_pointer = self.vars[ident];
-        let pointee_type = self.context.i64_type();
+        Ok(self.build_typed_load(typ, var_pointer, &variable_reference.ident().inner))
+    }
 
-        Ok(self
-            .builder
-            .build_load(pointee_type, var_pointer, ident)
-            .into())
+    /// Get the pointer to the memory referenced by a variable (or by an element of it, for
+    /// arrays), alongside the type stored there.
+    fn compile_variable_pointer(
+        &mut self,
+        variable_reference: &VariableReferenceExpression,
+    ) -> Result<(PointerValue<'static>, Type)> {
+        let Spanned { inner: ident, span } = variable_reference.ident();
+
+        let ScopedVariable {
+            declaration: VariableDeclaration { typ, array_len, .. },
+            pointer: var_pointer,
+        } =
+            self.type_system.scopes.get(ident).cloned().ok_or_else(|| {
+                CompileTimeError::UsingUndefinedVariable(ident.to_string()).at(*span)
+            })?;
+
+        match (variable_reference, array_len) {
+            (VariableReferenceExpression::Normal(_), None) => Ok((var_pointer, typ)),
+            (VariableReferenceExpression::Normal(_), Some(_)) => {
+                Err(CompileTimeError::ArrayVariableUsedWithoutIndex(ident.to_string()).at(*span))
+            }
+            (VariableReferenceExpression::Array(..), None) => {
+                Err(CompileTimeError::IndexingNonArrayVariable(ident.to_string()).at(*span))
+            }
+            (VariableReferenceExpression::Array(_, index), Some(array_len)) => {
+                let LlvmExpr::Int(index_value) = self.compile_expression(index)? else {
+                    return Err(CompileTimeError::ArrayIndexIsNotInt.at(index.span));
+                };
+
+                let element_type = self.llvm_type(typ).expect("variables can't be void");
+                let array_type = element_type.array_type(array_len as u32);
+                let zero = self.context.i64_type().const_zero();
+
+                // Safety: like in C, indexes aren't bounds checked
+                let element_pointer = unsafe {
+                    self.builder
+                        .build_gep(array_type, var_pointer, &[zero, index_value], "")
+                };
+
+                Ok((element_pointer, typ))
+            }
+        }
+    }
+
+    /// The LLVM type of the values of a mini-c type, `void` has no values.
+    ///
+    /// Every conversion between the two type systems goes through here, so `bool` is always an
+    /// `i1` and `int` is always an `i64`.
+    fn llvm_type(&self, typ: Type) -> Option<IntType<'static>> {
+        match typ {
+            Type::Void => None,
+            Type::Int => Some(self.context.i64_type()),
+            Type::Bool => Some(self.context.bool_type()),
+        }
+    }
+
+    fn llvm_function_type(
+        &self,
+        return_type: Type,
+        parameter_types: &[BasicMetadataTypeEnum<'static>],
+    ) -> FunctionType<'static> {
+        match self.llvm_type(return_type) {
+            Some(typ) => typ.fn_type(parameter_types, false),
+            None => self.context.void_type().fn_type(parameter_types, false),
+        }
+    }
+
+    /// Load a value of a variable, typed by its declaration instead of guessed from LLVM's type.
+    fn build_typed_load(&self, typ: Type, pointer: PointerValue<'static>, name: &str) -> LlvmExpr {
+        let llvm_type = self.llvm_type(typ).expect("variables can't be void");
+        let value = self.builder.build_load(llvm_type, pointer, name);
+
+        LlvmExpr::with_type(typ, Some(value.into_int_value()))
+    }
+
+    fn build_typed_store(&self, pointer: PointerValue<'static>, value: LlvmExpr) {
+        let value = value.to_value().expect("void values can't be stored");
+        self.builder.build_store(pointer, value);
     }
 
     fn new_block_name(&mut self, name: &str) -> String {
@@ -603,89 +1217,80 @@ impl Default for Compiler {
 }
 
 struct TypeSystem {
-    scopes: TypeSystemScopes,
-    functions: HashMap<Ident, FunctionDeclaration>,
+    scopes: SymbolTable<ScopedVariable>,
+    functions: FunctionTable,
+}
+
+/// A declared variable and the storage that was allocated for it.
+#[derive(Clone)]
+struct ScopedVariable {
+    declaration: VariableDeclaration,
+    pointer: PointerValue<'static>,
+}
+
+impl Symbol for ScopedVariable {
+    fn ident(&self) -> &Spanned<Ident> {
+        &self.declaration.ident
+    }
 }
 
 impl TypeSystem {
     fn new() -> Self {
         Self {
-            scopes: TypeSystemScopes::new(),
-            functions: HashMap::default(),
+            scopes: SymbolTable::new(),
+            functions: FunctionTable::new(),
         }
     }
 
+    /// Declare a function, or check it against a previous prototype of it.
     fn declare_function(&mut self, declaration: &FunctionDeclaration) -> Result<()> {
-        let ident = declaration.ident().clone();
+        let Err(mismatch) = self.functions.declare(declaration) else {
+            return Ok(());
+        };
 
-        match self.functions.insert(ident.clone(), declaration.clone()) {
-            Some(previous_declaration) => {
-                Err(CompileTimeError::RedefinedFunction {
-                    previous: previous_declaration,
+        let Spanned { inner: ident, span } = declaration.ident().clone();
+        let error = match mismatch {
+            PrototypeMismatch::Redefined => {
+                // Unwrap safety: only a previous declaration can be redefined
+                let previous = self.functions.get(&ident).unwrap().clone();
+                CompileTimeError::RedefinedFunction {
+                    previous,
                     new: declaration.clone(),
-                })
+                }
             }
-            None => Ok(()),
-        }
+            PrototypeMismatch::ReturnType => {
+                CompileTimeError::FunctionDefinitionWithDifferentReturnType(ident)
+            }
+            PrototypeMismatch::ParameterCount => {
+                CompileTimeError::FunctionDefinitionWithDifferentParameterCount(ident)
+            }
+            PrototypeMismatch::ParameterType(position) => {
+                CompileTimeError::FunctionDefinitionWithDifferentParameterType { ident, position }
+            }
+        };
+
+        Err(error.at(span))
     }
 
-    fn declare_builtin_function(
+    /// Declare a variable in the innermost scope, variables of outer scopes can be shadowed.
+    fn declare_variable(
         &mut self,
-        typ: Type,
-        ident: Ident,
-        parameters: Vec<Parameter>,
+        declaration: VariableDeclaration,
+        pointer: PointerValue<'static>,
     ) -> Result<()> {
-        let dummy_scope = Scope(vec![], vec![]);
-
-        let declaration = FunctionDeclaration(typ, ident, parameters, dummy_scope);
-
-        self.declare_function(&declaration)
-    }
-}
-
-struct TypeSystemScopes {
-    scopes_of_variables: Vec<Vec<VariableDeclaration>>,
-}
-
-impl TypeSystemScopes {
-    fn new() -> Self {
-        Self {
-            scopes_of_variables: vec![],
-        }
-    }
-
-    fn push_scope(&mut self) {
-        self.scopes_of_variables.push(vec![])
-    }
-
-    fn pop_scope(&mut self) {
-        self.scopes_of_variables.pop();
-    }
-
-    fn get_variable(&self, ident: &Ident) -> Option<&VariableDeclaration> {
-        self.scopes_of_variables
-            .iter()
-            .rev()
-            .flat_map(|scope| scope.iter())
-            .find(|var| &var.ident == ident)
-    }
+        let span = declaration.ident.span;
+        let variable = ScopedVariable {
+            declaration: declaration.clone(),
+            pointer,
+        };
 
-    fn try_declare_variable(&mut self, declaration: VariableDeclaration) -> Result<()> {
-        match self.get_variable(&declaration.ident) {
-            Some(previous_declaration) => {
-                Err(CompileTimeError::RedefinedVariable {
-                    previous: previous_declaration.clone(),
-                    new: declaration,
-                })
-            }
-            None => {
-                self.scopes_of_variables
-                    .last_mut()
-                    .unwrap()
-                    .push(declaration);
-                Ok(())
-            }
-        }
+        self.scopes.declare(variable).map_err(|previous| {
+            let error = CompileTimeError::RedefinedVariable {
+                previous: previous.declaration.clone(),
+                new: declaration,
+            };
+            error.at(span)
+        })
     }
 }
 
@@ -697,6 +1302,15 @@ enum LlvmExpr {
 }
 
 impl LlvmExpr {
+    /// Wrap a value whose mini-c type is known, `None` is the lack of value of `void`.
+    fn with_type(typ: Type, value: Option<IntValue<'static>>) -> Self {
+        match (typ, value) {
+            (Type::Void, _) | (_, None) => Self::Void,
+            (Type::Int, Some(value)) => Self::Int(value),
+            (Type::Bool, Some(value)) => Self::Bool(value),
+        }
+    }
+
     fn to_type(self) -> Type {
         match self {
             Self::Void => Type::Void,
@@ -720,28 +1334,6 @@ impl LlvmExpr {
     }
 }
 
-type TryAsBasicValueReturnType<'a> = Either<BasicValueEnum<'a>, InstructionValue<'a>>;
-impl From<TryAsBasicValueReturnType<'static>> for LlvmExpr {
-    fn from(value: TryAsBasicValueReturnType<'static>) -> Self {
-        match value {
-            Either::Left(value) => Self::from(value.into_int_value()),
-            Either::Right(_) => LlvmExpr::Void,
-        }
-    }
-}
-
-impl From<BasicMetadataValueEnum<'static>> for LlvmExpr {
-    fn from(value: BasicMetadataValueEnum<'static>) -> Self {
-        value.into_int_value().into()
-    }
-}
-
-impl From<BasicValueEnum<'static>> for LlvmExpr {
-    fn from(value: BasicValueEnum<'static>) -> Self {
-        value.into_int_value().into()
-    }
-}
-
 impl From<IntValue<'static>> for LlvmExpr {
     fn from(value: IntValue<'static>) -> Self {
         if value.get_type().get_bit_width() == 1 {
@@ -0,0 +1,30 @@
+; Implementation of the functions declared in "minicio.h", equivalent to `minicio.c`
+; at the root of the repository, but using `i64` for `int` like the rest of mini-c.
+
+@getint_format = private unnamed_addr constant [4 x i8] c"%ld\00"
+@putint_format = private unnamed_addr constant [5 x i8] c"%ld \00"
+@putnewline_format = private unnamed_addr constant [2 x i8] c"\0A\00"
+
+declare i32 @scanf(ptr, ...)
+declare i32 @printf(ptr, ...)
+
+define i64 @getint() {
+entry:
+  %ret = alloca i64, align 8
+  store i64 0, ptr %ret, align 8
+  call i32 (ptr, ...) @scanf(ptr @getint_format, ptr %ret)
+  %value = load i64, ptr %ret, align 8
+  ret i64 %value
+}
+
+define void @putint(i64 %0) {
+entry:
+  call i32 (ptr, ...) @printf(ptr @putint_format, i64 %0)
+  ret void
+}
+
+define void @putnewline() {
+entry:
+  call i32 (ptr, ...) @printf(ptr @putnewline_format)
+  ret void
+}
@@ -0,0 +1,214 @@
+use mini_c_ast::Span;
+use mini_c_diagnostics::Diagnostic;
+use mini_c_lexer::{Lexer, MiniCParseError, Token, TokenWithSpan};
+
+use crate::{lex, LalrpopError, ProgramParser};
+
+/// Only this many errors get their expected tokens checked, every check parses the input up to
+/// the error again, so checking all of them takes forever on a file full of errors.
+pub(crate) const CHECKED_ERRORS: usize = 10;
+
+const BINARY_OPERATORS: &[&str] = &[
+    "+", "-", "*", "/", "%", "&&", "||", "==", "!=", "<", "<=", ">", ">=",
+];
+const EXPRESSION_STARTS: &[&str] = &["!", "(", "-", "true", "false", "{ident}", "{int}"];
+
+/// A syntax error, described in words that make sense to the user.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxError {
+    pub message: String,
+    pub span: Span,
+}
+
+impl SyntaxError {
+    /// Describe an error of parsing `input`.
+    ///
+    /// LALRPOP's list of expected tokens isn't exact, so `input` is parsed again with each of
+    /// them inserted at the error, and only the ones that get the parser past it are listed.
+    pub fn new(error: LalrpopError<'_>, input: &str) -> Self {
+        Self::with_tokens(error, input, Some(&lex(input)))
+    }
+
+    /// Same as `new`, but reusing the `tokens` of `input`, without them the expected tokens are
+    /// listed as LALRPOP gives them.
+    pub(crate) fn with_tokens(
+        error: LalrpopError<'_>,
+        input: &str,
+        tokens: Option<&[TokenWithSpan<'_>]>,
+    ) -> Self {
+        let (message, span) = match error {
+            LalrpopError::UnrecognizedToken {
+                token: (start, Token::Invalid, end),
+                ..
+            } => {
+                let message = format!("invalid token '{}'", &input[start..end]);
+                (message, Span::new(start, end))
+            }
+            LalrpopError::UnrecognizedToken {
+                token: (start, token, end),
+                expected,
+            } => {
+                let expected = describe_expected(input, tokens, start, &expected);
+                (
+                    format!("{expected}, found '{token}'"),
+                    Span::new(start, end),
+                )
+            }
+            LalrpopError::UnrecognizedEOF { location, expected } => {
+                let expected = describe_expected(input, tokens, location, &expected);
+                (
+                    format!("{expected}, found end of file"),
+                    Span::new(location, location),
+                )
+            }
+            LalrpopError::ExtraToken {
+                token: (start, token, end),
+            } => (format!("unexpected '{token}'"), Span::new(start, end)),
+            LalrpopError::InvalidToken { location } => {
+                ("invalid token".to_owned(), Span::new(location, location))
+            }
+            LalrpopError::User { error } => describe_user_error(error),
+        };
+
+        Self { message, span }
+    }
+
+    pub fn into_diagnostic(self) -> Diagnostic {
+        Diagnostic::error(self.message).with_span(self.span)
+    }
+}
+
+fn describe_user_error(error: MiniCParseError) -> (String, Span) {
+    match error {
+        MiniCParseError::InvalidToken(text, range) => {
+            (format!("invalid token '{text}'"), span_of(range))
+        }
+        MiniCParseError::InvalidType(typ, range) => {
+            let message = format!("unknown type '{typ}', expected 'int', 'bool' or 'void'");
+            (message, span_of(range))
+        }
+        MiniCParseError::InvalidInteger(int, error, range) => {
+            (format!("invalid integer '{int}': {error}"), span_of(range))
+        }
+    }
+}
+
+fn span_of(range: std::ops::Range<usize>) -> Span {
+    Span::new(range.start, range.end)
+}
+
+/// Describe the terminals expected at `location`.
+fn describe_expected(
+    input: &str,
+    tokens: Option<&[TokenWithSpan<'_>]>,
+    location: usize,
+    expected: &[String],
+) -> String {
+    // They come quoted as written in the grammar
+    let terminals = expected
+        .iter()
+        .map(|terminal| {
+            terminal
+                .strip_prefix('"')
+                .and_then(|terminal| terminal.strip_suffix('"'))
+                .unwrap_or(terminal)
+                .replace(r#"\""#, "\"")
+        })
+        .filter(|terminal| {
+            tokens.is_none_or(|tokens| is_accepted(input, tokens, location, terminal))
+        })
+        .collect::<Vec<_>>();
+
+    // If a binary operator could come next, the parser just finished reading an expression,
+    // `-` doesn't count because it's also the unary negation
+    let after_expression = terminals
+        .iter()
+        .any(|terminal| terminal != "-" && BINARY_OPERATORS.contains(&terminal.as_str()));
+
+    // Listing everything that could continue the expression is just noise
+    let continues_expression = |terminal: &str| {
+        BINARY_OPERATORS.contains(&terminal) || ["=", "(", "["].contains(&terminal)
+    };
+    let mut terminals = terminals
+        .iter()
+        .map(String::as_str)
+        .filter(|terminal| !after_expression || !continues_expression(terminal))
+        .collect::<Vec<_>>();
+
+    // And so is listing everything that can start one
+    let starts_expression = EXPRESSION_STARTS
+        .iter()
+        .all(|start| terminals.contains(start));
+    if starts_expression {
+        terminals.retain(|terminal| !EXPRESSION_STARTS.contains(terminal));
+    }
+
+    let mut names = terminals
+        .into_iter()
+        .map(describe_terminal)
+        .collect::<Vec<_>>();
+    if starts_expression {
+        names.push("expression".to_owned());
+    }
+
+    let list = match names.as_slice() {
+        [] => "something else".to_owned(),
+        [name] => name.clone(),
+        [names @ .., last] => format!("{} or {last}", names.join(", ")),
+    };
+
+    if after_expression {
+        format!("expected {list} after expression")
+    } else {
+        format!("expected {list}")
+    }
+}
+
+/// Check if the parser gets past `location` when `terminal` is inserted there.
+///
+/// Whatever comes after `location` can't change that, so only the tokens before it are parsed.
+fn is_accepted(input: &str, tokens: &[TokenWithSpan<'_>], location: usize, terminal: &str) -> bool {
+    let inserted = match terminal {
+        "{ident}" => Token::Ident("x"),
+        "{int}" => Token::Int("0"),
+        terminal => {
+            match Lexer::new(terminal).next() {
+                Some(Ok((_, token, _))) => token,
+                // Not a token we know, keep it to be safe
+                _ => return true,
+            }
+        }
+    };
+
+    let tokens = tokens
+        .iter()
+        .copied()
+        .take_while(|(start, _, _)| *start < location)
+        .chain([(location, inserted, location)]);
+
+    let mut recovered_errors = vec![];
+    let result = ProgramParser::new().parse(input, &mut recovered_errors, tokens);
+
+    // The inserted token is the only one with an empty span
+    let is_inserted_token = |error: &LalrpopError| {
+        matches!(
+            error,
+            LalrpopError::UnrecognizedToken { token: (start, _, end), .. }
+                if *start == location && *end == location
+        )
+    };
+
+    !recovered_errors
+        .iter()
+        .map(|recovery| &recovery.error)
+        .chain(result.as_ref().err())
+        .any(is_inserted_token)
+}
+
+fn describe_terminal(terminal: &str) -> String {
+    match terminal {
+        "{ident}" => "identifier".to_owned(),
+        "{int}" => "integer".to_owned(),
+        terminal => format!("'{terminal}'"),
+    }
+}
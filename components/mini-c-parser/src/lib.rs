@@ -1,9 +1,15 @@
 //! The parser is written with LALRPOP.
 
+mod error;
+
 // Export
+pub use error::SyntaxError;
+use error::CHECKED_ERRORS;
 pub use lalrpop_codegen::mini_c_grammar::*;
+use mini_c_ast::Program;
 // wtf, why this here
 pub use mini_c_lexer::MiniCParseError;
+use mini_c_lexer::{Lexer, Token, TokenWithSpan};
 
 #[allow(warnings)]
 mod lalrpop_codegen {
@@ -14,8 +20,85 @@ mod lalrpop_codegen {
 
 pub type LalrpopError<'a> =
     lalrpop_util::ParseError<usize, mini_c_lexer::Token<'a>, MiniCParseError>;
+pub type LalrpopErrorRecovery<'a> =
+    lalrpop_util::ErrorRecovery<usize, mini_c_lexer::Token<'a>, MiniCParseError>;
+
+// Wrap a MiniCParseError in an Lalrpop error the parser recovered from, used in the grammar file.
+pub fn error(error: MiniCParseError) -> LalrpopErrorRecovery<'static> {
+    LalrpopErrorRecovery {
+        error: LalrpopError::User { error },
+        dropped_tokens: vec![],
+    }
+}
+
+// The error of a scope that ends at `location` without its `}`, used in the grammar file.
+pub fn missing_closing_brace(input: &str, location: usize) -> LalrpopErrorRecovery<'_> {
+    let expected = vec![r#""}""#.to_owned()];
+    let error = match Lexer::new(&input[location..]).next() {
+        Some(Ok((start, token, end))) => {
+            LalrpopError::UnrecognizedToken {
+                token: (location + start, token, location + end),
+                expected,
+            }
+        }
+        // Nothing but whitespace left, the file ends there
+        _ => LalrpopError::UnrecognizedEOF { location, expected },
+    };
+
+    LalrpopErrorRecovery {
+        error,
+        dropped_tokens: vec![],
+    }
+}
+
+/// Parse a program, recovering from syntax errors.
+///
+/// All syntax errors are returned, in the order they appear, alongside the program, where the
+/// parts that couldn't be parsed are replaced by `Error` nodes.
+pub fn parse(input: &str) -> (Program, Vec<SyntaxError>) {
+    let tokens = lex(input);
+    let mut recovered_errors = vec![];
+    let result = ProgramParser::new().parse(input, &mut recovered_errors, tokens.iter().copied());
+
+    let mut errors = recovered_errors
+        .into_iter()
+        .map(|recovery| recovery.error)
+        .collect::<Vec<_>>();
+
+    let program = result.unwrap_or_else(|error| {
+        errors.push(error);
+        Program {
+            preamble: false,
+            declarations: vec![],
+        }
+    });
+
+    // The recovered errors come in the order they were found
+    let mut errors = errors
+        .into_iter()
+        .enumerate()
+        .map(|(i, error)| {
+            let tokens = (i < CHECKED_ERRORS).then_some(tokens.as_slice());
+            SyntaxError::with_tokens(error, input, tokens)
+        })
+        .collect::<Vec<_>>();
+    errors.sort_by_key(|error| error.span.start());
+
+    (program, errors)
+}
 
-// Wrap a MiniCParseError in an Lalrpop error, used in the grammar file.
-pub fn error(error: MiniCParseError) -> LalrpopError<'static> {
-    LalrpopError::User { error }
+/// Invalid tokens are kept in the token stream, so the parser can recover from them like from
+/// any other unexpected token.
+pub(crate) fn lex(input: &str) -> Vec<TokenWithSpan<'_>> {
+    Lexer::new(input)
+        .map(|token| {
+            match token {
+                Ok(token) => token,
+                Err(MiniCParseError::InvalidToken(_, range)) => {
+                    (range.start, Token::Invalid, range.end)
+                }
+                Err(_) => unreachable!("the lexer only reports invalid tokens"),
+            }
+        })
+        .collect()
 }
@@ -0,0 +1,220 @@
+use std::time::{Duration, Instant};
+
+use mini_c_ast::{Declaration, FunctionDeclaration, Scope, Span, Spanned, Statement};
+use mini_c_parser::SyntaxError;
+
+fn syntax_errors(input: &str) -> Vec<SyntaxError> {
+    let (_, errors) = mini_c_parser::parse(input);
+    assert!(!errors.is_empty(), "Expected syntax errors");
+    errors
+}
+
+fn syntax_error(input: &str) -> SyntaxError {
+    let errors = syntax_errors(input);
+    assert_eq!(errors.len(), 1, "Expected a single error, got {errors:?}");
+    errors.into_iter().next().unwrap()
+}
+
+#[test]
+fn missing_semicolon_after_expression() {
+    let error = syntax_error("int main() { int a; a = 1 }");
+
+    assert_eq!(error.message, "expected ';' after expression, found '}'");
+    assert_eq!(error.span, Span::new(26, 27));
+}
+
+#[test]
+fn missing_expression() {
+    let error = syntax_error("int main() { int a; a = ; }");
+
+    assert_eq!(error.message, "expected expression, found ';'");
+}
+
+#[test]
+fn unclosed_call() {
+    let error = syntax_error("int main() { f(1 }");
+
+    assert_eq!(
+        error.message,
+        "expected ')' or ',' after expression, found '}'"
+    );
+}
+
+#[test]
+fn unexpected_end_of_file() {
+    let error = syntax_error("void f()");
+
+    assert_eq!(error.message, "expected ';' or '{', found end of file");
+    assert_eq!(error.span, Span::new(8, 8));
+}
+
+#[test]
+fn invalid_token() {
+    let error = syntax_error("int main() { int a; a = 1 @ 2; }");
+
+    assert_eq!(error.message, "invalid token '@'");
+    assert_eq!(error.span, Span::new(26, 27));
+}
+
+#[test]
+fn invalid_type() {
+    let error = syntax_error("float f() {}");
+
+    assert_eq!(
+        error.message,
+        "unknown type 'float', expected 'int', 'bool' or 'void'"
+    );
+    assert_eq!(error.span, Span::new(0, 5));
+}
+
+#[test]
+fn invalid_integer() {
+    let error = syntax_error("int main() { int a; a = 99999999999999999999; }");
+
+    assert_eq!(
+        error.message,
+        "invalid integer '99999999999999999999': number too large to fit in target type"
+    );
+    assert_eq!(error.span, Span::new(24, 44));
+}
+
+#[test]
+fn recovers_from_missing_semicolons() {
+    let errors = syntax_errors("int main() { int a; a = 1 a = 2 return a; }");
+
+    let messages = errors
+        .iter()
+        .map(|error| &error.message)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        [
+            "expected ';' after expression, found 'a'",
+            "expected ';' after expression, found 'return'",
+        ]
+    );
+}
+
+#[test]
+fn broken_statement_is_replaced_by_error_node() {
+    let (program, errors) = mini_c_parser::parse("int main() { int a; a = = 1; a = 2; }");
+    assert_eq!(errors.len(), 1);
+
+    let [Declaration::Function(function)] = program.declarations.as_slice() else {
+        panic!("Expected a single function, got {:?}", program.declarations);
+    };
+    let statements = &function.3.as_ref().unwrap().1;
+    assert!(matches!(statements[0].inner, Statement::Error));
+    assert!(matches!(
+        statements.last().unwrap().inner,
+        Statement::Expression(_)
+    ));
+}
+
+#[test]
+fn recovers_from_missing_closing_brace() {
+    let (program, errors) = mini_c_parser::parse("int f() { return 1;");
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].message, "expected '}', found end of file");
+    assert_eq!(errors[0].span, Span::new(19, 19));
+    assert!(matches!(
+        program.declarations.as_slice(),
+        [Declaration::Function(FunctionDeclaration(_, _, _, Some(Scope(_, statements))))]
+            if matches!(statements.as_slice(), [Spanned { inner: Statement::Return(_), .. }])
+    ));
+}
+
+#[test]
+fn closes_every_open_scope_at_end_of_file() {
+    let input = "int f() { int a; a = 1; while (true) { if (a > 0) { a = 2;";
+    let (program, errors) = mini_c_parser::parse(input);
+
+    assert_eq!(errors.len(), 1, "Expected a single error, got {errors:?}");
+    assert_eq!(errors[0].message, "expected '}', found end of file");
+    let [Declaration::Function(FunctionDeclaration(_, _, _, Some(Scope(vars, statements))))] =
+        program.declarations.as_slice()
+    else {
+        panic!("Expected a single function, got {:?}", program.declarations);
+    };
+    assert_eq!(vars.len(), 1);
+    assert!(matches!(
+        statements.as_slice(),
+        [
+            Spanned {
+                inner: Statement::Expression(_),
+                ..
+            },
+            Spanned {
+                inner: Statement::While(_),
+                ..
+            },
+        ]
+    ));
+}
+
+#[test]
+fn reports_every_invalid_token() {
+    let errors = syntax_errors("int main() { int a; a = 1 @ 2 $ 3; }");
+
+    let messages = errors
+        .iter()
+        .map(|error| &error.message)
+        .collect::<Vec<_>>();
+    assert_eq!(messages, ["invalid token '@'", "invalid token '$'"]);
+}
+
+#[test]
+fn missing_closing_brace_of_function() {
+    let input = "int f() { if (true) { return 1; } int main() { return 0; }";
+    let (program, errors) = mini_c_parser::parse(input);
+
+    assert_eq!(errors.len(), 1, "Expected a single error, got {errors:?}");
+    assert_eq!(errors[0].message, "expected '}', found 'int'");
+    assert_eq!(errors[0].span, Span::new(34, 37));
+
+    // Parsing continues at the next function
+    assert!(matches!(
+        program.declarations.as_slice(),
+        [Declaration::Function(_), Declaration::Function(_)]
+    ));
+}
+
+#[test]
+fn prototypes_have_no_body() {
+    let (program, errors) = mini_c_parser::parse("int f(int x); int f(int x) { return x; }");
+    assert!(errors.is_empty(), "{errors:?}");
+
+    assert!(matches!(
+        program.declarations.as_slice(),
+        [
+            Declaration::Function(FunctionDeclaration(.., None)),
+            Declaration::Function(FunctionDeclaration(.., Some(_))),
+        ]
+    ));
+}
+
+#[test]
+fn many_errors_are_described_quickly() {
+    // 2000 lines, every tenth one is missing its `;`
+    let statements = (0..2000)
+        .map(|i| if i % 10 == 0 { "a = 1\n" } else { "a = 1;\n" })
+        .collect::<String>();
+    let input = format!("int main() {{\nint a;\n{statements}return 0;\n}}");
+
+    let start = Instant::now();
+    let errors = syntax_errors(&input);
+    let elapsed = start.elapsed();
+
+    assert_eq!(errors.len(), 200);
+    assert!(elapsed < Duration::from_secs(5), "Took {elapsed:?}");
+    // Past the first errors the expected tokens are listed as LALRPOP gives them
+    assert_eq!(
+        errors[0].message,
+        "expected ';' after expression, found 'a'"
+    );
+    assert_eq!(
+        errors[199].message,
+        "expected ')', ',', ';' or ']' after expression, found 'a'"
+    );
+}
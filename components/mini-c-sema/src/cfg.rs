@@ -0,0 +1,155 @@
+//! Control flow graphs of function bodies.
+
+use mini_c_ast::{
+    Expression, ForStatement, IfStatement, Scope, Spanned, Statement, Value, WhileStatement,
+};
+
+type BlockId = usize;
+
+// Every graph starts with these two blocks
+const ENTRY: BlockId = 0;
+const END: BlockId = 1;
+
+/// The control flow graph of a function body.
+///
+/// Blocks are sequences of statements without jumps in between, they're connected by the jumps
+/// of `if`s, loops and `break`s. A `return` leaves the function, so its block has no successors,
+/// while the end of the body flows into the special `END` block. Only the shape of the graph is
+/// kept, not the statements.
+#[derive(Debug)]
+pub struct ControlFlowGraph {
+    // The successors of each block
+    blocks: Vec<Vec<BlockId>>,
+}
+
+impl ControlFlowGraph {
+    pub fn of_function(body: &Scope) -> Self {
+        let mut builder = Builder {
+            graph: Self {
+                blocks: vec![vec![], vec![]],
+            },
+            current: ENTRY,
+            loop_exits: vec![],
+        };
+
+        builder.build_scope(body);
+        builder.jump(builder.current, END);
+        builder.graph
+    }
+
+    /// If some path through the function reaches the end of its body without a `return`.
+    pub fn falls_off_end(&self) -> bool {
+        let mut visited = vec![false; self.blocks.len()];
+        let mut stack = vec![ENTRY];
+
+        while let Some(block) = stack.pop() {
+            if block == END {
+                return true;
+            }
+            if !visited[block] {
+                visited[block] = true;
+                stack.extend(&self.blocks[block]);
+            }
+        }
+
+        false
+    }
+}
+
+/// If a loop with this condition is only left through a `break`, that's the case for loops
+/// without a condition, or with a `true` one.
+pub fn is_infinite_loop(condition: Option<&Spanned<Expression>>) -> bool {
+    match condition {
+        None => true,
+        Some(condition) => matches!(condition.inner, Expression::Value(Value::Bool(true))),
+    }
+}
+
+struct Builder {
+    graph: ControlFlowGraph,
+    // The block that the next statement goes into
+    current: BlockId,
+    // Where a `break` jumps to, for each loop being built, the innermost is the last
+    loop_exits: Vec<BlockId>,
+}
+
+impl Builder {
+    fn new_block(&mut self) -> BlockId {
+        self.graph.blocks.push(vec![]);
+        self.graph.blocks.len() - 1
+    }
+
+    fn jump(&mut self, from: BlockId, to: BlockId) {
+        self.graph.blocks[from].push(to);
+    }
+
+    fn build_scope(&mut self, Scope(_, statements): &Scope) {
+        for statement in statements {
+            self.build_statement(&statement.inner);
+        }
+    }
+
+    fn build_statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::If(IfStatement(_, then_scope, else_scope)) => {
+                let condition = self.current;
+                let join = self.new_block();
+
+                self.current = self.new_block();
+                self.jump(condition, self.current);
+                self.build_scope(then_scope);
+                self.jump(self.current, join);
+
+                match else_scope {
+                    Some(else_scope) => {
+                        self.current = self.new_block();
+                        self.jump(condition, self.current);
+                        self.build_scope(else_scope);
+                        self.jump(self.current, join);
+                    }
+                    None => self.jump(condition, join),
+                }
+
+                self.current = join;
+            }
+            Statement::For(ForStatement((_, condition, _), scope)) => {
+                self.build_loop(condition.as_ref(), scope);
+            }
+            Statement::While(WhileStatement(condition, scope)) => {
+                self.build_loop(condition.as_ref(), scope);
+            }
+            // It might have been a return, don't report a missing one because of a syntax error
+            Statement::Return(_) | Statement::Error => {
+                // Code after it is unreachable, it goes into a block without predecessors
+                self.current = self.new_block();
+            }
+            Statement::Break => {
+                // A `break` outside of a loop was already reported, it just ends the block
+                if let Some(&exit) = self.loop_exits.last() {
+                    self.jump(self.current, exit);
+                }
+                self.current = self.new_block();
+            }
+            Statement::Scope(scope) => self.build_scope(scope),
+            Statement::Expression(_) => {}
+        }
+    }
+
+    fn build_loop(&mut self, condition: Option<&Spanned<Expression>>, body: &Scope) {
+        let [header, body_start, exit] = [(); 3].map(|()| self.new_block());
+        self.jump(self.current, header);
+        self.jump(header, body_start);
+
+        if !is_infinite_loop(condition) {
+            self.jump(header, exit);
+        }
+
+        self.loop_exits.push(exit);
+        self.current = body_start;
+        self.build_scope(body);
+        self.jump(self.current, header);
+        self.loop_exits.pop();
+
+        self.current = exit;
+    }
+}
@@ -0,0 +1,241 @@
+use std::fmt;
+
+use mini_c_ast::{BinaryOperator, Ident, Span, Type};
+use mini_c_diagnostics::Diagnostic;
+
+/// Errors found by the semantic analysis, the ones listed by A4 are displayed with the exact
+/// wording it requires.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SemanticError {
+    // Declarations
+    RedefinedVariable {
+        ident: Ident,
+        previous: Span,
+    },
+    FunctionDefinitionWithDifferentReturnType(Ident),
+    FunctionDefinitionWithDifferentParameterCount(Ident),
+    FunctionDefinitionWithDifferentParameterType {
+        ident: Ident,
+        position: usize,
+    },
+    RedefinedFunction {
+        ident: Ident,
+        /// Builtins have no previous definition in the source.
+        previous: Option<Span>,
+    },
+    VariableIsVoid(Ident),
+    FunctionParameterIsVoid {
+        function_ident: Ident,
+        parameter_ident: Ident,
+    },
+
+    // Returns
+    MissingReturn(Ident),
+    ReturnWithValueInVoidFunction,
+    ReturnWithoutValueInNonVoidFunction,
+    ReturnTypeMismatch {
+        expected: Type,
+        got: Type,
+    },
+
+    // Statements
+    BreakOutsideOfLoop,
+    IfConditionIsNotBool,
+    ForConditionIsNotBool,
+    WhileConditionIsNotBool,
+
+    // Expressions
+    NegateOperandIsNotInt,
+    NotOperandIsNotBool,
+    LogicalOperandIsNotBool,
+    EqualityOperandsMismatch,
+    BinaryOperandIsNotInt(BinaryOperator),
+    UndeclaredVariable(Ident),
+    ArrayIndexIsNotInt,
+    IndexingNonArrayVariable(Ident),
+    ArrayVariableUsedWithoutIndex(Ident),
+    AssignmentTypeMismatch,
+    IntegerLiteralOutOfRange,
+
+    // Function calls
+    UndeclaredFunction(Ident),
+    CallArgumentCountMismatch {
+        ident: Ident,
+        expected: usize,
+        got: usize,
+    },
+    CallArgumentTypeMismatch {
+        ident: Ident,
+        position: usize,
+    },
+}
+
+impl fmt::Display for SemanticError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RedefinedVariable { ident, .. } => {
+                write!(
+                    f,
+                    "Redefinition of variable/parameter \"{ident}\" in the same scope!"
+                )
+            }
+            Self::FunctionDefinitionWithDifferentReturnType(ident) => {
+                write!(
+                    f,
+                    "Definition of function \"{ident}()\" with different return type!"
+                )
+            }
+            Self::FunctionDefinitionWithDifferentParameterCount(ident) => {
+                write!(
+                    f,
+                    "Definition of function \"{ident}()\" with different number of parameters!"
+                )
+            }
+            Self::FunctionDefinitionWithDifferentParameterType { ident, position } => {
+                write!(
+                    f,
+                    "Definition of function \"{ident}()\" with different parameter type at \
+                     position {position}!"
+                )
+            }
+            Self::RedefinedFunction { ident, .. } => {
+                write!(f, "Redefinition of function \"{ident}()\"!")
+            }
+            Self::VariableIsVoid(ident) => write!(f, "Variable \"{ident}\" has void type!"),
+            Self::FunctionParameterIsVoid {
+                function_ident,
+                parameter_ident,
+            } => {
+                write!(
+                    f,
+                    "Parameter \"{parameter_ident}\" of function \"{function_ident}()\" has void \
+                     type!"
+                )
+            }
+            Self::MissingReturn(ident) => {
+                write!(
+                    f,
+                    "The function \"{ident}()\" need to return a value at its end!"
+                )
+            }
+            Self::ReturnWithValueInVoidFunction => {
+                write!(
+                    f,
+                    "Function has void return type, but the return statement has a returned \
+                     expression!"
+                )
+            }
+            Self::ReturnWithoutValueInNonVoidFunction => {
+                write!(
+                    f,
+                    "Function has non-void return type, but the return statement has no returned \
+                     expression!"
+                )
+            }
+            Self::ReturnTypeMismatch { expected, got } => {
+                write!(
+                    f,
+                    "Function has return type \"{expected}\", but the returned expression has \
+                     type \"{got}\"!"
+                )
+            }
+            Self::BreakOutsideOfLoop => {
+                write!(
+                    f,
+                    "Break statement must appear inside a for/while statement!"
+                )
+            }
+            Self::IfConditionIsNotBool => {
+                write!(
+                    f,
+                    "Conditional expression in if statement has non-bool type!"
+                )
+            }
+            Self::ForConditionIsNotBool => {
+                write!(
+                    f,
+                    "Conditional expression in for statement has non-bool type!"
+                )
+            }
+            Self::WhileConditionIsNotBool => {
+                write!(
+                    f,
+                    "Conditional expression in while statement has non-bool type!"
+                )
+            }
+            Self::NegateOperandIsNotInt => write!(f, "Negate \"-\" opcode must have int operand!"),
+            Self::NotOperandIsNotBool => write!(f, "Not \"!\" opcode must have bool operand!"),
+            Self::LogicalOperandIsNotBool => {
+                write!(f, "\"&&\"/\"||\" opcode must have bool operand!")
+            }
+            Self::EqualityOperandsMismatch => {
+                write!(
+                    f,
+                    "\"==\"/\"!=\" opcode must have same primitive type operand!"
+                )
+            }
+            Self::BinaryOperandIsNotInt(operator) => {
+                write!(f, "\"{operator}\" opcode must have int type operand!")
+            }
+            Self::UndeclaredVariable(ident) => {
+                write!(f, "Variable {ident} is not declared before use!")
+            }
+            Self::ArrayIndexIsNotInt => write!(f, "Array index expressions must have int operand!"),
+            Self::IndexingNonArrayVariable(_) => write!(f, "Indexing an non-array variable!"),
+            Self::ArrayVariableUsedWithoutIndex(ident) => {
+                write!(f, "Array variable {ident} must be indexed before use!")
+            }
+            Self::AssignmentTypeMismatch => {
+                write!(
+                    f,
+                    "Variable and the assignment expression do not have the same type!"
+                )
+            }
+            Self::IntegerLiteralOutOfRange => {
+                write!(f, "Integer literal must be inside the range of int!")
+            }
+            Self::UndeclaredFunction(ident) => {
+                write!(f, "Function {ident}() is not declared before use!")
+            }
+            Self::CallArgumentCountMismatch {
+                ident,
+                expected,
+                got,
+            } => {
+                write!(
+                    f,
+                    "Function {ident}() is declared with {expected} parameters but called with \
+                     {got} arguments!"
+                )
+            }
+            Self::CallArgumentTypeMismatch { ident, position } => {
+                write!(
+                    f,
+                    "Function {ident}() does not match the type of the call argument at position \
+                     {position}!"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for SemanticError {}
+
+impl SemanticError {
+    /// Turn the error into a diagnostic pointing at `span`, with labels for the other places
+    /// involved in it.
+    pub fn into_diagnostic(self, span: Span) -> Diagnostic {
+        let diagnostic = Diagnostic::error(self.to_string()).with_span(span);
+
+        match self {
+            Self::RedefinedVariable { previous, .. } => {
+                diagnostic.with_label(previous, "previous declaration here")
+            }
+            Self::RedefinedFunction {
+                previous: Some(previous),
+                ..
+            } => diagnostic.with_label(previous, "previous definition here"),
+            _ => diagnostic,
+        }
+    }
+}
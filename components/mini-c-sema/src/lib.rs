@@ -0,0 +1,520 @@
+//! Semantic analysis of the AST, it checks types, declarations and control flow rules before
+//! any backend sees the program.
+
+pub mod cfg;
+mod error;
+pub mod symbols;
+
+use mini_c_ast::{
+    AssignmentExpression, BinaryExpression, BinaryOperator, Declaration, Expression, ForStatement,
+    FunctionCallExpression, FunctionDeclaration, IfStatement, Parameter, Program, ReturnStatement,
+    Scope, Span, Spanned, Statement, Type, UnaryExpression, UnaryOperator, Value,
+    VariableDeclaration, VariableReferenceExpression, WhileStatement,
+};
+
+pub use self::error::SemanticError;
+use self::{
+    cfg::ControlFlowGraph,
+    symbols::{builtin_functions, FunctionTable, PrototypeMismatch, SymbolTable},
+};
+
+/// Check that the program follows all the semantic rules of mini-c.
+///
+/// Checking doesn't stop at the first error, all errors found are returned, in the order they
+/// appear in the program, alongside the span of the construct that caused them.
+pub fn check(program: &Program) -> Result<(), Vec<Spanned<SemanticError>>> {
+    let mut checker = Checker::new();
+    checker.check_program(program);
+    // Functions are declared before their bodies are checked, so errors aren't found in order
+    checker.errors.sort_by_key(|error| error.span.start());
+
+    if checker.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(checker.errors)
+    }
+}
+
+struct Checker {
+    functions: FunctionTable,
+    variables: SymbolTable<VariableDeclaration>,
+    // Return type of the function being checked
+    return_type: Type,
+    // How many loops we're currently inside of
+    loop_depth: usize,
+    // Errors found so far
+    errors: Vec<Spanned<SemanticError>>,
+}
+
+impl Checker {
+    fn new() -> Self {
+        Self {
+            functions: FunctionTable::new(),
+            variables: SymbolTable::new(),
+            return_type: Type::Void,
+            loop_depth: 0,
+            errors: vec![],
+        }
+    }
+
+    fn report(&mut self, error: SemanticError, span: Span) {
+        self.errors.push(Spanned::new(error, span));
+    }
+
+    fn check_program(&mut self, program: &Program) {
+        for builtin in builtin_functions(program.preamble) {
+            self.declare_function(&builtin);
+        }
+
+        // Functions can be called before their definition, so declare them all first
+        for declaration in &program.declarations {
+            if let Declaration::Function(function) = declaration {
+                self.declare_function(function);
+            }
+        }
+
+        // The outermost scope holds the global variables
+        self.variables.push_scope();
+
+        for declaration in &program.declarations {
+            match declaration {
+                Declaration::Variable(variables) => {
+                    for variable in variables {
+                        self.declare_variable(variable);
+                    }
+                }
+                Declaration::Function(function @ FunctionDeclaration(.., Some(scope))) => {
+                    self.check_function(function, scope);
+                }
+                // Prototypes were already checked by `declare_function`
+                Declaration::Function(FunctionDeclaration(.., None)) => {}
+                // Already reported by the parser
+                Declaration::Error => {}
+            }
+        }
+
+        self.variables.pop_scope();
+    }
+
+    /// Declare a function, or check it against a previous prototype of it.
+    fn declare_function(&mut self, declaration: &FunctionDeclaration) {
+        let Err(mismatch) = self.functions.declare(declaration) else {
+            return;
+        };
+
+        let Spanned { inner: ident, span } = declaration.ident().clone();
+        let error = match mismatch {
+            PrototypeMismatch::Redefined => {
+                // Builtins aren't in the source, so there's nothing to point at
+                let previous = self
+                    .functions
+                    .get(&ident)
+                    .map(|previous| previous.ident().span)
+                    .filter(|span| *span != Span::default());
+                SemanticError::RedefinedFunction { ident, previous }
+            }
+            PrototypeMismatch::ReturnType => {
+                SemanticError::FunctionDefinitionWithDifferentReturnType(ident)
+            }
+            PrototypeMismatch::ParameterCount => {
+                SemanticError::FunctionDefinitionWithDifferentParameterCount(ident)
+            }
+            PrototypeMismatch::ParameterType(position) => {
+                SemanticError::FunctionDefinitionWithDifferentParameterType { ident, position }
+            }
+        };
+
+        self.report(error, span);
+    }
+
+    fn check_function(&mut self, function: &FunctionDeclaration, scope: &Scope) {
+        let FunctionDeclaration(return_type, function_ident, parameters, _) = function;
+
+        self.return_type = *return_type;
+        // Parameters share the scope of the function body
+        self.variables.push_scope();
+
+        for Parameter(typ, ident) in parameters {
+            if *typ == Type::Void {
+                self.report(
+                    SemanticError::FunctionParameterIsVoid {
+                        function_ident: function_ident.inner.clone(),
+                        parameter_ident: ident.inner.clone(),
+                    },
+                    ident.span,
+                );
+            }
+
+            self.declare_variable(&VariableDeclaration {
+                typ: *typ,
+                ident: ident.clone(),
+                array_len: None,
+            });
+        }
+
+        self.check_scope_body(scope);
+
+        let needs_return = *return_type != Type::Void && function_ident.inner != "main";
+        if needs_return && ControlFlowGraph::of_function(scope).falls_off_end() {
+            self.report(
+                SemanticError::MissingReturn(function_ident.inner.clone()),
+                function_ident.span,
+            );
+        }
+
+        self.variables.pop_scope();
+    }
+
+    /// Declare a variable in the innermost scope, variables of outer scopes can be shadowed.
+    fn declare_variable(&mut self, declaration: &VariableDeclaration) {
+        let Spanned { inner: ident, span } = &declaration.ident;
+
+        if declaration.typ == Type::Void {
+            self.report(SemanticError::VariableIsVoid(ident.clone()), *span);
+        }
+
+        if let Err(previous) = self.variables.declare(declaration.clone()) {
+            let error = SemanticError::RedefinedVariable {
+                ident: ident.clone(),
+                previous: previous.ident.span,
+            };
+            self.report(error, *span);
+        }
+    }
+
+    fn check_scope(&mut self, scope: &Scope) {
+        self.variables.push_scope();
+        self.check_scope_body(scope);
+        self.variables.pop_scope();
+    }
+
+    fn check_scope_body(&mut self, scope: &Scope) {
+        let Scope(variable_declarations, statements) = scope;
+
+        for declaration in variable_declarations {
+            self.declare_variable(declaration);
+        }
+        for statement in statements {
+            self.check_statement(statement);
+        }
+    }
+
+    fn check_statement(&mut self, statement: &Spanned<Statement>) {
+        match &statement.inner {
+            Statement::If(statement) => self.check_if(statement),
+            Statement::For(statement) => self.check_for(statement),
+            Statement::While(statement) => self.check_while(statement),
+            Statement::Break => {
+                if self.loop_depth == 0 {
+                    self.report(SemanticError::BreakOutsideOfLoop, statement.span);
+                }
+            }
+            Statement::Return(return_statement) => {
+                self.check_return(return_statement, statement.span)
+            }
+            Statement::Scope(scope) => self.check_scope(scope),
+            Statement::Expression(expression) => drop(self.check_expression(expression)),
+            Statement::Error => {}
+        }
+    }
+
+    fn check_if(&mut self, statement: &IfStatement) {
+        let IfStatement(condition, then_scope, else_scope) = statement;
+
+        if mismatches(self.check_expression(condition), Type::Bool) {
+            self.report(SemanticError::IfConditionIsNotBool, condition.span);
+        }
+
+        self.check_scope(then_scope);
+        if let Some(else_scope) = else_scope {
+            self.check_scope(else_scope);
+        }
+    }
+
+    fn check_for(&mut self, statement: &ForStatement) {
+        let ForStatement((initialization, condition, step), scope) = statement;
+
+        if let Some(initialization) = initialization {
+            self.check_expression(initialization);
+        }
+        if let Some(condition) = condition {
+            if mismatches(self.check_expression(condition), Type::Bool) {
+                self.report(SemanticError::ForConditionIsNotBool, condition.span);
+            }
+        }
+        if let Some(step) = step {
+            self.check_expression(step);
+        }
+
+        self.check_loop_scope(scope);
+    }
+
+    fn check_while(&mut self, statement: &WhileStatement) {
+        let WhileStatement(condition, scope) = statement;
+
+        if let Some(condition) = condition {
+            if mismatches(self.check_expression(condition), Type::Bool) {
+                self.report(SemanticError::WhileConditionIsNotBool, condition.span);
+            }
+        }
+
+        self.check_loop_scope(scope);
+    }
+
+    fn check_loop_scope(&mut self, scope: &Scope) {
+        self.loop_depth += 1;
+        self.check_scope(scope);
+        self.loop_depth -= 1;
+    }
+
+    fn check_return(&mut self, statement: &ReturnStatement, span: Span) {
+        let ReturnStatement(expression) = statement;
+
+        match (self.return_type, expression) {
+            (Type::Void, None) => {}
+            (Type::Void, Some(expression)) => {
+                self.check_expression(expression);
+                self.report(
+                    SemanticError::ReturnWithValueInVoidFunction,
+                    expression.span,
+                );
+            }
+            (_, None) => self.report(SemanticError::ReturnWithoutValueInNonVoidFunction, span),
+            (expected, Some(expression)) => {
+                let got = self.check_expression(expression);
+                if let Some(got) = got.filter(|&got| got != expected) {
+                    self.report(
+                        SemanticError::ReturnTypeMismatch { expected, got },
+                        expression.span,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Check an expression and return its type, or `None` if it couldn't be determined
+    /// because of an error.
+    fn check_expression(&mut self, expression: &Spanned<Expression>) -> Option<Type> {
+        let span = expression.span;
+
+        match &expression.inner {
+            Expression::Value(value) => Some(self.check_value(value, false, span)),
+            Expression::Assignment(assignment) => self.check_assignment(assignment, span),
+            Expression::Binary(expression) => Some(self.check_binary(expression, span)),
+            Expression::Unary(expression) => Some(self.check_unary(expression, span)),
+            Expression::FunctionCall(call) => self.check_function_call(call, span),
+            Expression::VariableReference(variable_reference) => {
+                self.check_variable_reference(variable_reference)
+            }
+        }
+    }
+
+    /// Integer literals follow the range of a 32-bit C `int`, even though the code generator
+    /// stores them in 64 bits.
+    fn check_value(&mut self, value: &Value, is_negated: bool, span: Span) -> Type {
+        match value {
+            Value::Int(int) => {
+                let max = if is_negated {
+                    -i64::from(i32::MIN)
+                } else {
+                    i64::from(i32::MAX)
+                };
+
+                if *int > max {
+                    self.report(SemanticError::IntegerLiteralOutOfRange, span);
+                }
+                Type::Int
+            }
+            Value::Bool(_) => Type::Bool,
+        }
+    }
+
+    fn check_assignment(&mut self, assignment: &AssignmentExpression, span: Span) -> Option<Type> {
+        let AssignmentExpression(variable_reference, expression) = assignment;
+
+        let expected_type = self.check_variable_reference(variable_reference);
+        let got_type = self.check_expression(expression);
+
+        if let (Some(expected_type), Some(got_type)) = (expected_type, got_type) {
+            if got_type != expected_type {
+                self.report(SemanticError::AssignmentTypeMismatch, span);
+            }
+        }
+
+        expected_type
+    }
+
+    /// The type of a binary expression only depends on the operator, even if its operands
+    /// are wrong.
+    fn check_binary(&mut self, expression: &BinaryExpression, span: Span) -> Type {
+        let BinaryExpression(lhs, operator, rhs) = expression;
+
+        let lhs = self.check_expression(lhs);
+        let rhs = self.check_expression(rhs);
+
+        match operator {
+            BinaryOperator::And | BinaryOperator::Or => {
+                if mismatches(lhs, Type::Bool) || mismatches(rhs, Type::Bool) {
+                    self.report(SemanticError::LogicalOperandIsNotBool, span);
+                }
+                Type::Bool
+            }
+            BinaryOperator::Equals | BinaryOperator::NotEquals => {
+                if let (Some(lhs), Some(rhs)) = (lhs, rhs) {
+                    if lhs != rhs || lhs == Type::Void {
+                        self.report(SemanticError::EqualityOperandsMismatch, span);
+                    }
+                }
+                Type::Bool
+            }
+            BinaryOperator::Greater
+            | BinaryOperator::GreaterOrEquals
+            | BinaryOperator::Less
+            | BinaryOperator::LessOrEquals => {
+                if mismatches(lhs, Type::Int) || mismatches(rhs, Type::Int) {
+                    self.report(SemanticError::BinaryOperandIsNotInt(operator.clone()), span);
+                }
+                Type::Bool
+            }
+            BinaryOperator::Add
+            | BinaryOperator::Sub
+            | BinaryOperator::Mul
+            | BinaryOperator::Div
+            | BinaryOperator::Modulo => {
+                if mismatches(lhs, Type::Int) || mismatches(rhs, Type::Int) {
+                    self.report(SemanticError::BinaryOperandIsNotInt(operator.clone()), span);
+                }
+                Type::Int
+            }
+        }
+    }
+
+    fn check_unary(&mut self, expression: &UnaryExpression, span: Span) -> Type {
+        let UnaryExpression(operator, operand) = expression;
+
+        // The negated literal can reach one past the positive range of int
+        let operand = match (operator, &operand.inner) {
+            (UnaryOperator::Negative, Expression::Value(value)) => {
+                Some(self.check_value(value, true, operand.span))
+            }
+            _ => self.check_expression(operand),
+        };
+
+        match operator {
+            UnaryOperator::Negative => {
+                if mismatches(operand, Type::Int) {
+                    self.report(SemanticError::NegateOperandIsNotInt, span);
+                }
+                Type::Int
+            }
+            UnaryOperator::Not => {
+                if mismatches(operand, Type::Bool) {
+                    self.report(SemanticError::NotOperandIsNotBool, span);
+                }
+                Type::Bool
+            }
+        }
+    }
+
+    fn check_function_call(&mut self, call: &FunctionCallExpression, span: Span) -> Option<Type> {
+        let FunctionCallExpression(function_ident, arguments) = call;
+
+        let argument_types = arguments
+            .iter()
+            .map(|argument| self.check_expression(argument))
+            .collect::<Vec<_>>();
+
+        let Some(FunctionDeclaration(return_type, _, parameters, _)) =
+            self.functions.get(&function_ident.inner).cloned()
+        else {
+            self.report(
+                SemanticError::UndeclaredFunction(function_ident.inner.clone()),
+                function_ident.span,
+            );
+            return None;
+        };
+
+        if parameters.len() != arguments.len() {
+            self.report(
+                SemanticError::CallArgumentCountMismatch {
+                    ident: function_ident.inner.clone(),
+                    expected: parameters.len(),
+                    got: arguments.len(),
+                },
+                span,
+            );
+            return Some(return_type);
+        }
+
+        for (position, ((Parameter(param_type, _), argument), argument_type)) in parameters
+            .iter()
+            .zip(arguments)
+            .zip(argument_types)
+            .enumerate()
+        {
+            if mismatches(argument_type, *param_type) {
+                self.report(
+                    SemanticError::CallArgumentTypeMismatch {
+                        ident: function_ident.inner.clone(),
+                        position,
+                    },
+                    argument.span,
+                );
+            }
+        }
+
+        Some(return_type)
+    }
+
+    fn check_variable_reference(
+        &mut self,
+        variable_reference: &VariableReferenceExpression,
+    ) -> Option<Type> {
+        let Spanned { inner: ident, span } = variable_reference.ident();
+
+        let index = match variable_reference {
+            VariableReferenceExpression::Normal(_) => None,
+            VariableReferenceExpression::Array(_, index) => {
+                Some((self.check_expression(index), index.span))
+            }
+        };
+
+        let Some(VariableDeclaration { typ, array_len, .. }) = self.variables.get(ident).cloned()
+        else {
+            self.report(SemanticError::UndeclaredVariable(ident.clone()), *span);
+            return None;
+        };
+
+        match (index, array_len) {
+            (None, None) => Some(typ),
+            (None, Some(_)) => {
+                self.report(
+                    SemanticError::ArrayVariableUsedWithoutIndex(ident.clone()),
+                    *span,
+                );
+                None
+            }
+            (Some(_), None) => {
+                self.report(
+                    SemanticError::IndexingNonArrayVariable(ident.clone()),
+                    *span,
+                );
+                None
+            }
+            (Some((index_type, index_span)), Some(_)) => {
+                if mismatches(index_type, Type::Int) {
+                    self.report(SemanticError::ArrayIndexIsNotInt, index_span);
+                }
+                Some(typ)
+            }
+        }
+    }
+}
+
+/// If the type is known and differs from the expected one.
+///
+/// Unknown types come from expressions that already had an error reported, they're accepted
+/// anywhere to avoid reporting the same mistake many times.
+fn mismatches(typ: Option<Type>, expected: Type) -> bool {
+    matches!(typ, Some(typ) if typ != expected)
+}
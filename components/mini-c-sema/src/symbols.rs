@@ -0,0 +1,200 @@
+//! Tables of the variables and functions declared in a program, shared by every pass that needs
+//! to know what an identifier refers to.
+
+use std::collections::HashMap;
+
+use mini_c_ast::{FunctionDeclaration, Ident, Parameter, Scope, Span, Spanned, Type};
+
+/// Anything that can be declared in a scope, each pass keeps its own information about them.
+pub trait Symbol {
+    fn ident(&self) -> &Spanned<Ident>;
+}
+
+impl Symbol for mini_c_ast::VariableDeclaration {
+    fn ident(&self) -> &Spanned<Ident> {
+        &self.ident
+    }
+}
+
+/// Variables declared in nested scopes, the innermost scope is the last one.
+#[derive(Debug)]
+pub struct SymbolTable<T> {
+    scopes: Vec<Vec<T>>,
+}
+
+impl<T: Symbol> SymbolTable<T> {
+    pub fn new() -> Self {
+        Self { scopes: vec![] }
+    }
+
+    pub fn push_scope(&mut self) {
+        self.scopes.push(vec![]);
+    }
+
+    /// Leave the innermost scope, returning what was declared in it.
+    pub fn pop_scope(&mut self) -> Vec<T> {
+        self.scopes.pop().expect("popped more scopes than pushed")
+    }
+
+    /// Declare a symbol in the innermost scope, symbols of outer scopes can be shadowed.
+    ///
+    /// If the innermost scope already has one with the same name, that one is returned instead.
+    pub fn declare(&mut self, symbol: T) -> Result<(), &T> {
+        let current_scope = self
+            .scopes
+            .last_mut()
+            .expect("declared a symbol outside of any scope");
+
+        match current_scope
+            .iter()
+            .position(|previous| previous.ident().inner == symbol.ident().inner)
+        {
+            Some(previous) => Err(&current_scope[previous]),
+            None => {
+                current_scope.push(symbol);
+                Ok(())
+            }
+        }
+    }
+
+    /// Find the innermost symbol with this name, respecting shadowing.
+    pub fn get(&self, ident: &str) -> Option<&T> {
+        self.scopes
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.iter().rev())
+            .find(|symbol| symbol.ident().inner == ident)
+    }
+
+    pub fn get_mut(&mut self, ident: &str) -> Option<&mut T> {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .flat_map(|scope| scope.iter_mut().rev())
+            .find(|symbol| symbol.ident().inner == ident)
+    }
+
+    /// Find the symbol that one with this name would shadow, from the scopes outside of the
+    /// innermost one.
+    pub fn get_shadowed(&self, ident: &str) -> Option<&T> {
+        self.scopes
+            .iter()
+            .rev()
+            .skip(1)
+            .flat_map(|scope| scope.iter().rev())
+            .find(|symbol| symbol.ident().inner == ident)
+    }
+}
+
+impl<T: Symbol> Default for SymbolTable<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How a declaration of a function disagrees with a previous one of the same function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrototypeMismatch {
+    /// Both have a body.
+    Redefined,
+    ReturnType,
+    ParameterCount,
+    /// Position of the first parameter with a different type, starting at 0.
+    ParameterType(usize),
+}
+
+/// Check that a declaration of a function agrees with a previous one, only one of them can be
+/// a definition.
+pub fn check_prototype(
+    previous: &FunctionDeclaration,
+    new: &FunctionDeclaration,
+) -> Result<(), PrototypeMismatch> {
+    let FunctionDeclaration(previous_type, _, previous_parameters, previous_body) = previous;
+    let FunctionDeclaration(new_type, _, new_parameters, new_body) = new;
+
+    let different_parameter_position = previous_parameters.iter().zip(new_parameters).position(
+        |(Parameter(previous_type, _), Parameter(new_type, _))| previous_type != new_type,
+    );
+
+    if previous_body.is_some() && new_body.is_some() {
+        Err(PrototypeMismatch::Redefined)
+    } else if previous_type != new_type {
+        Err(PrototypeMismatch::ReturnType)
+    } else if previous_parameters.len() != new_parameters.len() {
+        Err(PrototypeMismatch::ParameterCount)
+    } else if let Some(position) = different_parameter_position {
+        Err(PrototypeMismatch::ParameterType(position))
+    } else {
+        Ok(())
+    }
+}
+
+/// Functions declared in a program, by name. Functions are global, so there are no scopes.
+#[derive(Debug, Default)]
+pub struct FunctionTable {
+    functions: HashMap<Ident, FunctionDeclaration>,
+}
+
+impl FunctionTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare a function, or check it against a previous prototype of it.
+    ///
+    /// When they don't agree, the previous one is kept, so it can still be found with `get`.
+    pub fn declare(&mut self, declaration: &FunctionDeclaration) -> Result<(), PrototypeMismatch> {
+        let ident = &declaration.ident().inner;
+
+        if let Some(previous) = self.functions.get(ident) {
+            check_prototype(previous, declaration)?;
+
+            // Keep the definition instead of the prototype
+            if let FunctionDeclaration(.., None) = declaration {
+                return Ok(());
+            }
+        }
+
+        self.functions.insert(ident.clone(), declaration.clone());
+        Ok(())
+    }
+
+    pub fn get(&self, ident: &str) -> Option<&FunctionDeclaration> {
+        self.functions.get(ident)
+    }
+}
+
+/// The functions that are available without being defined, `minicio.h` ones are only available
+/// when the program includes it.
+pub fn builtin_functions(preamble: bool) -> Vec<FunctionDeclaration> {
+    // Builtins aren't in the source code, so they have no meaningful span
+    let spanned = |ident: &str| Spanned::new(ident.to_owned(), Span::default());
+
+    let builtin = |typ, ident, parameters| {
+        // Builtins are already defined, so users can't write another definition for them
+        FunctionDeclaration(typ, spanned(ident), parameters, Some(Scope(vec![], vec![])))
+    };
+
+    let mut builtins = vec![
+        builtin(
+            Type::Void,
+            "print",
+            vec![Parameter(Type::Int, spanned("input"))],
+        ),
+        builtin(Type::Void, "println", vec![]),
+    ];
+
+    if preamble {
+        builtins.extend([
+            builtin(Type::Int, "getint", vec![]),
+            builtin(
+                Type::Void,
+                "putint",
+                vec![Parameter(Type::Int, spanned("value"))],
+            ),
+            builtin(Type::Void, "putnewline", vec![]),
+        ]);
+    }
+
+    builtins
+}
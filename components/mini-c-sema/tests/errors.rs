@@ -0,0 +1,242 @@
+use std::path::PathBuf;
+
+use mini_c_ast::{LineIndex, Spanned};
+use mini_c_sema::SemanticError;
+
+fn check(input: &str) -> Result<(), Vec<Spanned<SemanticError>>> {
+    let (ast, syntax_errors) = mini_c_parser::parse(input);
+    assert!(
+        syntax_errors.is_empty(),
+        "Failed to parse program: {syntax_errors:?}"
+    );
+
+    mini_c_sema::check(&ast)
+}
+
+fn check_error_messages(input: &str) -> Vec<String> {
+    check(input)
+        .expect_err("Expected semantic errors")
+        .iter()
+        .map(|error| error.inner.to_string())
+        .collect()
+}
+
+fn check_error_message(input: &str) -> String {
+    let messages = check_error_messages(input);
+    assert_eq!(
+        messages.len(),
+        1,
+        "Expected a single error, got {messages:?}"
+    );
+    messages.into_iter().next().unwrap()
+}
+
+const EXAMPLES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../examples");
+
+// Examples that show errors instead of working programs
+const INVALID_EXAMPLES: &[&str] = &["chained_functions.c"];
+
+fn example_paths() -> Vec<PathBuf> {
+    let mut paths = std::fs::read_dir(EXAMPLES)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths
+}
+
+#[test]
+fn examples_pass() {
+    let is_valid = |path: &PathBuf| {
+        let file_name = path.file_name().unwrap().to_str().unwrap();
+        !INVALID_EXAMPLES.contains(&file_name)
+    };
+
+    for path in example_paths().into_iter().filter(is_valid) {
+        let input = std::fs::read_to_string(&path).unwrap();
+
+        if let Err(errors) = check(&input) {
+            panic!("{}: {errors:?}", path.display());
+        }
+    }
+}
+
+#[test]
+fn chained_functions_example_calls_undeclared_function() {
+    let input = std::fs::read_to_string(format!("{EXAMPLES}/chained_functions.c")).unwrap();
+
+    assert_eq!(
+        check_error_messages(&input),
+        [
+            "Function i() is not declared before use!",
+            "Function i() is not declared before use!",
+        ]
+    );
+}
+
+#[test]
+fn return_type_mismatch() {
+    let message = check_error_message("bool f() { return 1; }");
+
+    assert_eq!(
+        message,
+        "Function has return type \"bool\", but the returned expression has type \"int\"!"
+    );
+}
+
+#[test]
+fn redefined_variable_in_same_scope() {
+    let message = check_error_message("void f(int a) { int a; }");
+
+    assert_eq!(
+        message,
+        "Redefinition of variable/parameter \"a\" in the same scope!"
+    );
+}
+
+#[test]
+fn shadowing_in_inner_scope_is_allowed() {
+    check("int a; void f() { int a; { bool a; a = true; } a = 1; }").unwrap();
+}
+
+#[test]
+fn break_outside_of_loop() {
+    let message = check_error_message("void f() { break; }");
+
+    assert_eq!(
+        message,
+        "Break statement must appear inside a for/while statement!"
+    );
+}
+
+#[test]
+fn comparison_operand_is_not_int() {
+    let message = check_error_message("void f() { bool b; b = true >= 1; }");
+
+    assert_eq!(message, "\">=\" opcode must have int type operand!");
+}
+
+#[test]
+fn call_argument_count_mismatch() {
+    let message = check_error_message("int f(int a) { return a; } int main() { f(); }");
+
+    assert_eq!(
+        message,
+        "Function f() is declared with 1 parameters but called with 0 arguments!"
+    );
+}
+
+#[test]
+fn prototype_with_different_parameter_type() {
+    let message = check_error_message("int f(int a); int f(bool a) { return 1; }");
+
+    assert_eq!(
+        message,
+        "Definition of function \"f()\" with different parameter type at position 0!"
+    );
+}
+
+#[test]
+fn minicio_requires_preamble() {
+    let message = check_error_message("int main() { putnewline(); }");
+
+    assert_eq!(message, "Function putnewline() is not declared before use!");
+}
+
+#[test]
+fn integer_literal_range() {
+    check("int main() { int a; a = -2147483648; }").unwrap();
+
+    let message = check_error_message("int main() { int a; a = 2147483648; }");
+    assert_eq!(message, "Integer literal must be inside the range of int!");
+}
+
+#[test]
+fn multiple_errors_are_reported() {
+    let messages = check_error_messages(
+        "int main() { int a; a = -true; if (a) { break; } a = undefined + 1; }",
+    );
+
+    assert_eq!(
+        messages,
+        [
+            "Negate \"-\" opcode must have int operand!",
+            "Conditional expression in if statement has non-bool type!",
+            "Break statement must appear inside a for/while statement!",
+            "Variable undefined is not declared before use!",
+        ]
+    );
+}
+
+#[test]
+fn errors_point_at_line_and_column() {
+    let input = "int main() {\n    int a;\n    a = undefined;\n}";
+    let errors = check(input).unwrap_err();
+
+    let line_index = LineIndex::new(input);
+    let positions = errors
+        .iter()
+        .map(|error| line_index.line_column(error.span.start()).to_string())
+        .collect::<Vec<_>>();
+
+    assert_eq!(positions, ["3:9"]);
+}
+
+#[test]
+fn errors_are_in_source_order() {
+    let input = "int main() { x = 1; return 0; } int f(int a); int f(bool a) { return 1; }";
+    let errors = check(input).unwrap_err();
+
+    let line_index = LineIndex::new(input);
+    let positions = errors
+        .iter()
+        .map(|error| line_index.line_column(error.span.start()).to_string())
+        .collect::<Vec<_>>();
+
+    assert_eq!(positions, ["1:14", "1:51"]);
+}
+
+#[test]
+fn returns_in_every_branch() {
+    let inputs = [
+        "int f(bool c) { if (c) { return 1; } else { return 2; } }",
+        "int f(bool c) { if (c) { return 1; } else { { return 2; } } }",
+        "int f() { while (true) { return 1; } }",
+        "int f() { int i; for (;;) { i = 1; } }",
+        "int f(bool c) { while () { if (c) { break; } } return 1; }",
+    ];
+
+    for input in inputs {
+        if let Err(errors) = check(input) {
+            panic!("{input}: {errors:?}");
+        }
+    }
+}
+
+#[test]
+fn paths_falling_off_the_end() {
+    let inputs = [
+        "int f(bool c) { if (c) { return 1; } }",
+        "int f(bool c) { while (c) { return 1; } }",
+        "int f(bool c) { while (true) { if (c) { break; } return 1; } }",
+        "int f(bool c) { for (;;) { break; return 1; } }",
+    ];
+
+    for input in inputs {
+        assert_eq!(
+            check_error_message(input),
+            "The function \"f()\" need to return a value at its end!",
+            "{input}"
+        );
+    }
+}
+
+#[test]
+fn break_outside_of_loop_in_function_needing_return() {
+    let message = check_error_message("int f() { break; } int main() { return 0; }");
+
+    assert_eq!(
+        message,
+        "Break statement must appear inside a for/while statement!"
+    );
+}
@@ -1,88 +1,115 @@
 use std::{fs, path::PathBuf};
 
-use mini_c_lexer::Lexer;
-use mini_c_llvm_codegen::Compiler;
-use mini_c_parser::ProgramParser;
+use mini_c_ast::Spanned;
+use mini_c_diagnostics::{Diagnostic, Renderer};
+use mini_c_lint::{Level, LintLevels};
+use mini_c_llvm_codegen::{CompileTimeError, Compiler};
 
 fn main() {
-    let files = get_file_args();
+    let (files, lint_levels) = parse_args();
 
     for path in files {
         let input = fs::read_to_string(&path).expect("Failed to read file");
 
-        let lexer = Lexer::new(&input);
-        let ast = ProgramParser::new()
-            .parse(&input, lexer)
-            .expect("Failed to parse program");
+        let renderer = Renderer::new(&path, &input);
+
+        let (ast, syntax_errors) = mini_c_parser::parse(&input);
+
+        // The semantic analysis would only repeat the syntax errors with other words
+        if !syntax_errors.is_empty() {
+            for error in syntax_errors {
+                eprint!("{}", renderer.render(&error.into_diagnostic()));
+            }
+            std::process::exit(1);
+        }
+
+        if let Err(errors) = mini_c_sema::check(&ast) {
+            for error in errors {
+                let diagnostic = error.inner.into_diagnostic(error.span);
+                eprint!("{}", renderer.render(&diagnostic));
+            }
+            std::process::exit(1);
+        }
+
+        let lint_diagnostics = mini_c_lint::lint(&ast, &lint_levels);
+        for diagnostic in &lint_diagnostics {
+            eprint!("{}", renderer.render(diagnostic));
+        }
+        if lint_diagnostics.iter().any(Diagnostic::is_error) {
+            std::process::exit(1);
+        }
 
         let mut compiler = Compiler::new();
-        compiler.compile(&ast).expect("Failed to compile program");
+        if let Err(error) = compiler.compile(&ast) {
+            eprint!("{}", renderer.render(&compile_time_error_diagnostic(error)));
+            std::process::exit(1);
+        }
         compiler.run();
     }
 }
 
-fn get_file_args() -> Vec<PathBuf> {
-    // Run on all examples
-    let argv = std::env::args()
-        .skip(1)
-        .map(PathBuf::from)
-        .collect::<Vec<PathBuf>>();
-
-    match argv.as_slice() {
-        [] => {
-            glob::glob("examples/*")
-                .unwrap()
-                .map(Result::unwrap)
-                .collect()
+/// The semantic analysis catches these errors first, but the backend still points at the code
+/// that caused them, and redefinitions at both declarations.
+fn compile_time_error_diagnostic(error: Spanned<CompileTimeError>) -> Diagnostic {
+    let Spanned { inner: error, span } = error;
+    let diagnostic = Diagnostic::error(error.to_string()).with_span(span);
+
+    match error {
+        CompileTimeError::RedefinedVariable { previous, .. } => {
+            diagnostic.with_label(previous.ident.span, "previous declaration here")
+        }
+        CompileTimeError::RedefinedFunction { previous, .. } => {
+            diagnostic.with_label(previous.ident().span, "previous definition here")
         }
-        _ => argv,
+        _ => diagnostic,
     }
 }
 
-// A4 Error statements
-// The error statements that your program should print out is below. After the error statement, the compiler should show which line and column the error occured in parenthesis in the same line:
-// Function has return type "y", but the returned expression has type "x"! (line:col)
-// e.g. Function has return type "bool", but the returned expression has type "int"! (13:8)
-
-// The provided public tester also shows the correct format of error statements, but the order of the statements do not matter.
-// You have to replace the placeholders (name, x, y) with the correct names in the code as well.
-
-// Error statements:
-
-// Redefinition of variable/parameter "name" in the same scope!
-// Definition of function "name()" with different return type!
-// Definition of function "name()" with different number of parameters!
-// Definition of function "name()" with different parameter type at position x!
-// Redefinition of function "name()"!
-
-// The function "name()" need to return a value at its end!
-// Function has void return type, but the return statement has a returned expression!
-// Function has non-void return type, but the return statement has no returned expression!
-// Function has return type "y", but the returned expression has type "x"!
-
-// Break statement must appear inside a for/while statement!
-
-// Conditional expression in if statement has non-bool type!
-// Conditional expression in for statement has non-bool type!
-// Conditional expression in while statement has non-bool type!
-// Negate "-" opcode must have int operand!
-// Not "!" opcode must have bool operand!
-// "&&"/"||" opcode must have bool operand!
-// "=="/"!=" opcode must have same primitive type operand!
-// ">" opcode must have int type operand!
-// "<" opcode must have int type operand!
-// ">=" opcode must have int type operand!
-// "<=" opcode must have int type operand!
-// Variable name is not declared before use!
-// Array index expressions must have int operand!
-// Indexing an non-array variable!
-// Variable and the assignment expression do not have the same type!
-// Integer literal must be inside the range of int!
-// "+" opcode must have int type operand!
-// "-" opcode must have int type operand!
-// "*" opcode must have int type operand!
-// "/" opcode must have int type operand!
-
-// Function name() is not declared before use!
-// Function name() is declared with x parameters but called with y arguments!
-// Function name() does not match the type of the call argument at position x!
+/// Read the lint flags and the files to compile.
+///
+/// `-A`, `-W` and `-D` allow, warn or deny the lint that follows them, `warnings` refers to
+/// all lints. Everything else is a file, when there are none, run on all examples.
+fn parse_args() -> (Vec<PathBuf>, LintLevels) {
+    let mut files = vec![];
+    let mut lint_levels = LintLevels::default();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let level = match arg.as_str() {
+            "-A" => Level::Allow,
+            "-W" => Level::Warn,
+            "-D" => Level::Deny,
+            _ => {
+                files.push(PathBuf::from(arg));
+                continue;
+            }
+        };
+
+        let Some(name) = args.next() else {
+            eprintln!("error: expected a lint name after `{arg}`");
+            std::process::exit(2);
+        };
+
+        if name == "warnings" {
+            lint_levels.set_all(level);
+            continue;
+        }
+
+        match name.parse() {
+            Ok(lint) => lint_levels.set(lint, level),
+            Err(error) => {
+                eprintln!("error: {error}");
+                std::process::exit(2);
+            }
+        }
+    }
+
+    if files.is_empty() {
+        files = glob::glob("examples/*")
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+    }
+
+    (files, lint_levels)
+}
@@ -0,0 +1,214 @@
+//! Errors found by the backend, the semantic analysis is skipped to make sure it finds them too.
+
+use mini_c::test_utils;
+use mini_c_ast::Spanned;
+use mini_c_llvm_codegen::{CompileTimeError, Compiler};
+
+fn compile_error(input: &str) -> Spanned<CompileTimeError> {
+    let ast = test_utils::generate_ast(input);
+
+    Compiler::new()
+        .compile(&ast)
+        .expect_err("Expected a compile time error")
+}
+
+fn compile_error_message(input: &str) -> String {
+    compile_error(input).inner.to_string()
+}
+
+/// The code that the error points at.
+fn compile_error_source(input: &str) -> &str {
+    let span = compile_error(input).span;
+    &input[span.start()..span.end()]
+}
+
+#[test]
+fn arithmetic_and_ordering_operands_must_be_ints() {
+    let message = compile_error_message("int main() { return 1 + true; }");
+    assert_eq!(message, "\"+\" opcode must have int type operand!");
+
+    let message = compile_error_message("int main() { if (true > false) {} return 0; }");
+    assert_eq!(message, "\">\" opcode must have int type operand!");
+}
+
+#[test]
+fn equality_operands_must_have_the_same_type() {
+    let message = compile_error_message("int main() { if (1 == true) {} return 0; }");
+    assert_eq!(
+        message,
+        "\"==\"/\"!=\" opcode must have same primitive type operand!"
+    );
+}
+
+#[test]
+fn bools_can_be_compared() {
+    let ast = test_utils::generate_ast(
+        "int main() { bool a; a = true; if (a != false == true) { return 1; } return 0; }",
+    );
+
+    let mut compiler = Compiler::new();
+    compiler.compile(&ast).unwrap();
+    compiler.verify().unwrap();
+    assert!(compiler.ir().contains("icmp ne i1"));
+}
+
+#[test]
+fn returns_must_match_the_return_type() {
+    let message = compile_error_message("void f() { return 5; } int main() { return 0; }");
+    assert_eq!(
+        message,
+        "Function has void return type, but the return statement has a returned expression!"
+    );
+
+    let message = compile_error_message("int f() { return; } int main() { return 0; }");
+    assert_eq!(
+        message,
+        "Function has non-void return type, but the return statement has no returned \
+         expression!"
+    );
+
+    let message = compile_error_message("int f() { return true; } int main() { return 0; }");
+    assert_eq!(
+        message,
+        "Function has return type \"int\", but the returned expression has type \"bool\"!"
+    );
+}
+
+#[test]
+fn calls_must_pass_one_argument_per_parameter() {
+    let message = compile_error_message("int f(int a) { return a; } int main() { return f(); }");
+    assert_eq!(
+        message,
+        "Function f() is declared with 1 parameters but called with 0 arguments!"
+    );
+
+    let message = compile_error_message("int main() { print(1, 2); return 0; }");
+    assert_eq!(
+        message,
+        "Function print() is declared with 1 parameters but called with 2 arguments!"
+    );
+
+    let message = compile_error_message("int main() { println(1); return 0; }");
+    assert_eq!(
+        message,
+        "Function println() is declared with 0 parameters but called with 1 arguments!"
+    );
+}
+
+#[test]
+fn functions_only_in_the_module_are_undefined() {
+    // `printf` and `scanf` come with `minicio.h`, but only its own functions can be called
+    let input = "#include \"minicio.h\" int main() { printf(1); return 0; }";
+
+    assert_eq!(
+        compile_error_message(input),
+        "Function printf() is not declared before use!"
+    );
+    assert_eq!(compile_error_source(input), "printf");
+}
+
+#[test]
+fn definitions_must_match_their_prototype() {
+    let message =
+        compile_error_message("int f(); bool f() { return true; } int main() { return 0; }");
+    assert_eq!(
+        message,
+        "Definition of function \"f()\" with different return type!"
+    );
+
+    let message =
+        compile_error_message("int f(int a); int f() { return 0; } int main() { return 0; }");
+    assert_eq!(
+        message,
+        "Definition of function \"f()\" with different number of parameters!"
+    );
+
+    let message = compile_error_message(
+        "int f(int a, int b); int f(int a, bool b) { return a; } int main() { return 0; }",
+    );
+    assert_eq!(
+        message,
+        "Definition of function \"f()\" with different parameter type at position 1!"
+    );
+}
+
+#[test]
+fn functions_can_only_be_defined_once() {
+    let message = compile_error_message(
+        "int f() { return 0; } int f() { return 1; } int main() { return 0; }",
+    );
+    assert_eq!(message, "Redefinition of function \"f()\"!");
+}
+
+#[test]
+fn prototypes_can_come_after_the_definition() {
+    let ast = test_utils::generate_ast(
+        "int f(int a) { return a; } int f(int a); int main() { return f(1); }",
+    );
+
+    let mut compiler = Compiler::new();
+    compiler.compile(&ast).unwrap();
+    compiler.verify().unwrap();
+    assert!(compiler.ir().contains("define i64 @f(i64 %0)"));
+}
+
+#[test]
+fn break_must_be_inside_a_loop() {
+    let message = compile_error_message("int main() { if (true) { break; } return 0; }");
+    assert_eq!(
+        message,
+        "Break statement must appear inside a for/while statement!"
+    );
+}
+
+#[test]
+fn logical_operands_must_be_bools() {
+    let message = compile_error_message("int main() { if (1 && true) {} return 0; }");
+    assert_eq!(message, "\"&&\"/\"||\" opcode must have bool operand!");
+
+    let message = compile_error_message("int main() { if (true || 0) {} return 0; }");
+    assert_eq!(message, "\"&&\"/\"||\" opcode must have bool operand!");
+}
+
+#[test]
+fn arrays_must_be_indexed_with_ints() {
+    let message = compile_error_message("int main() { int a[3]; a[true] = 1; return 0; }");
+    assert_eq!(message, "Array index expressions must have int operand!");
+
+    let message = compile_error_message("int main() { int a[3]; return a[false]; }");
+    assert_eq!(message, "Array index expressions must have int operand!");
+}
+
+#[test]
+fn only_arrays_can_be_indexed() {
+    let message = compile_error_message("int main() { int a; a[0] = 1; return 0; }");
+    assert_eq!(message, "Indexing an non-array variable!");
+
+    let message = compile_error_message("int main() { int a; return a[0]; }");
+    assert_eq!(message, "Indexing an non-array variable!");
+
+    let message = compile_error_message("int main() { int a[3]; return a; }");
+    assert_eq!(message, "Array variable a must be indexed before use!");
+}
+
+#[test]
+fn errors_point_at_the_code_that_caused_them() {
+    let source = compile_error_source("int main() { return 1 + (true && 2 > 1); }");
+    assert_eq!(source, "1 + (true && 2 > 1)");
+
+    let source = compile_error_source("int main() { while (1) {} return 0; }");
+    assert_eq!(source, "1");
+
+    let source = compile_error_source("int main() { if (true) { break; } return 0; }");
+    assert_eq!(source, "break;");
+
+    let source = compile_error_source("int main() { int a[2]; a[0] = f(a[true]); return 0; }");
+    assert_eq!(source, "f");
+
+    let source = compile_error_source("int main() { int a[2]; a[0] = a[true]; return 0; }");
+    assert_eq!(source, "true");
+
+    let source =
+        compile_error_source("int f(); bool f() { return true; } int main() { return 0; }");
+    assert_eq!(source, "f");
+}
@@ -0,0 +1,243 @@
+//! Checks the LLVM IR generated for the examples, without running it.
+
+use fs_err as fs;
+use mini_c::test_utils;
+use mini_c_llvm_codegen::Compiler;
+
+#[test]
+fn examples_generate_valid_ir() {
+    let mut paths = glob::glob("examples/*")
+        .unwrap()
+        .map(Result::unwrap)
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    for path in paths {
+        let ast = test_utils::generate_ast(fs::read_to_string(&path).unwrap());
+
+        // Like in the compiler, programs with semantic errors never reach the backend
+        if mini_c_sema::check(&ast).is_err() {
+            continue;
+        }
+
+        let mut compiler = Compiler::new();
+        if let Err(error) = compiler.compile(&ast) {
+            panic!("{}: {}", path.display(), error.inner);
+        }
+        if let Err(message) = compiler.verify() {
+            panic!("{}: {message}", path.display());
+        }
+    }
+}
+
+#[test]
+fn returns_inside_nested_scopes() {
+    let input = "
+        int f(int n) {
+            while (true) {
+                if (n > 10) {
+                    return n;
+                    n = 0;
+                } else {
+                    { return 0; }
+                }
+            }
+        }
+        int main() {
+            int i;
+            for (i = 0; i < 3; i = i + 1) {
+                break;
+                i = 5;
+            }
+            return f(i);
+        }
+    ";
+    let ast = test_utils::generate_ast(input);
+
+    let mut compiler = Compiler::new();
+    compiler.compile(&ast).unwrap();
+    compiler.verify().unwrap();
+}
+
+#[test]
+fn locals_are_allocated_in_the_entry_block() {
+    let input = "
+        int main() {
+            int i;
+            for (i = 0; i < 100000; i = i + 1) {
+                int a;
+                bool b[10];
+                a = i;
+                b[1] = a > 3;
+            }
+            return 0;
+        }
+    ";
+    let ast = test_utils::generate_ast(input);
+
+    let mut compiler = Compiler::new();
+    compiler.compile(&ast).unwrap();
+    compiler.verify().unwrap();
+
+    let ir = compiler.ir();
+    let main = &ir[ir.find("define i64 @main").unwrap()..];
+
+    let mut block = "";
+    for line in main.lines().take_while(|line| *line != "}") {
+        // Labels may be followed by a comment with the predecessors of the block
+        if let Some((label, _)) = line.split_once(':').filter(|_| !line.starts_with(' ')) {
+            block = label;
+        }
+        if line.contains("alloca") {
+            assert_eq!(block, "entry", "{line}");
+        }
+    }
+}
+
+#[test]
+fn bool_variables_are_i1() {
+    let input = "
+        bool flags[2];
+        bool not(bool b) { return !b; }
+        int main() {
+            bool b;
+            b = not(false);
+            flags[1] = b;
+            if (flags[1] && not(b) == false) {
+                return 1;
+            }
+            return 0;
+        }
+    ";
+    let ast = test_utils::generate_ast(input);
+
+    let mut compiler = Compiler::new();
+    compiler.compile(&ast).unwrap();
+    compiler.verify().unwrap();
+
+    let ir = compiler.ir();
+    assert!(ir.contains("define i1 @not(i1 %0)"), "{ir}");
+    assert!(ir.contains("load i1, ptr %b"), "{ir}");
+}
+
+#[test]
+fn shadowing_variables_have_their_own_storage() {
+    let input = "
+        int main() {
+            int x;
+            x = 7;
+            {
+                int x;
+                x = 9;
+            }
+            return x;
+        }
+    ";
+    let ast = test_utils::generate_ast(input);
+
+    let mut compiler = Compiler::new();
+    compiler.compile(&ast).unwrap();
+    compiler.verify().unwrap();
+
+    let ir = compiler.ir();
+    let main = &ir[ir.find("define i64 @main").unwrap()..];
+    let allocas = main
+        .lines()
+        .filter(|line| line.contains("alloca i64"))
+        .collect::<Vec<_>>();
+    assert_eq!(allocas.len(), 2, "{ir}");
+
+    // The pointer that each value is stored into
+    let pointer_of_store = |value: &str| {
+        let store = format!("store i64 {value}, ptr ");
+        let line = main.lines().find(|line| line.contains(&store)).unwrap();
+        line.split_once(&store)
+            .unwrap()
+            .1
+            .split(',')
+            .next()
+            .unwrap()
+    };
+    let outer = pointer_of_store("7");
+    let inner = pointer_of_store("9");
+    assert_ne!(outer, inner, "{ir}");
+
+    // And the return reads the outer one
+    assert!(main.contains(&format!("load i64, ptr {outer}")), "{ir}");
+}
+
+#[test]
+fn for_header_parts_are_optional() {
+    let input = "
+        int main() {
+            int i;
+            i = 0;
+            for (; i < 3; i = i + 1) {}
+            for (i = 0; ; i = i + 1) {
+                if (i > 3) { break; }
+            }
+            for (i = 0; i < 3;) {
+                i = i + 1;
+            }
+            for (;;) {
+                break;
+            }
+            return i;
+        }
+    ";
+    let ast = test_utils::generate_ast(input);
+
+    let mut compiler = Compiler::new();
+    compiler.compile(&ast).unwrap();
+    compiler.verify().unwrap();
+}
+
+#[test]
+fn break_leaves_the_innermost_loop() {
+    let input = "
+        int main() {
+            int i;
+            int j;
+            i = 0;
+            while (true) {
+                for (j = 0; j < 10; j = j + 1) {
+                    if (j == 5) { break; }
+                }
+                i = i + 1;
+                if (i == 3) { break; }
+            }
+            return i;
+        }
+    ";
+    let ast = test_utils::generate_ast(input);
+
+    let mut compiler = Compiler::new();
+    compiler.compile(&ast).unwrap();
+    compiler.verify().unwrap();
+
+    // Only `break`s jump to the end of a loop unconditionally, each one to its own loop
+    let ir = compiler.ir();
+    assert_eq!(ir.matches("br label %block_for_end_").count(), 1, "{ir}");
+    assert_eq!(ir.matches("br label %block_while_end_").count(), 1, "{ir}");
+}
+
+#[test]
+fn logical_operators_short_circuit() {
+    let input = "
+        bool both(bool a, bool b) { return a && b; }
+        bool either(bool a, bool b) { return a || b; }
+        int main() { return 0; }
+    ";
+    let ast = test_utils::generate_ast(input);
+
+    let mut compiler = Compiler::new();
+    compiler.compile(&ast).unwrap();
+    compiler.verify().unwrap();
+
+    // The right-hand side has its own block, skipped with the value decided by the left one
+    let ir = compiler.ir();
+    let both = &ir[ir.find("define i1 @both").unwrap()..ir.find("define i1 @either").unwrap()];
+    assert!(both.contains("phi i1 [ false, %entry ]"), "{ir}");
+    let either = &ir[ir.find("define i1 @either").unwrap()..];
+    assert!(either.contains("phi i1 [ true, %entry ]"), "{ir}");
+}